@@ -78,7 +78,7 @@ function App() {
     void analyzerRef.current?.playFrame(frame);
   }, []);
 
-  const { status, latestMetrics, latestParams, reconnect, disconnect } =
+  const { status, latestMetrics, latestParams, feed, reconnect, disconnect } =
     useAudioStream(selectedAsset, handleFrame);
 
   useEffect(() => {
@@ -135,6 +135,7 @@ function App() {
             metrics={latestMetrics}
             params={latestParams}
             status={status}
+            feed={feed}
           />
         </section>
         <section className="stage">
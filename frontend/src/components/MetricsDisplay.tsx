@@ -1,19 +1,33 @@
-import type { ConnectionStatus, MarketMetrics, MusicalParams } from '../types';
+import type {
+  ConnectionStatus,
+  FeedStatus,
+  MarketMetrics,
+  MusicalParams,
+} from '../types';
 
 interface Props {
   metrics: MarketMetrics | null;
   params: MusicalParams | null;
   status: ConnectionStatus;
+  feed: FeedStatus | null;
 }
 
 const format = (value?: number, digits = 2) =>
   typeof value === 'number' ? value.toFixed(digits) : '—';
 
-export const MetricsDisplay = ({ metrics, params, status }: Props) => (
+const feedLabel = (feed: FeedStatus | null) => {
+  if (!feed) {
+    return '—';
+  }
+  return feed.degraded ? `${feed.state} (degraded)` : feed.state;
+};
+
+export const MetricsDisplay = ({ metrics, params, status, feed }: Props) => (
   <div className="panel">
     <h2>Live Metrics</h2>
     <div className="metrics-grid">
       <Metric label="Status" value={status} />
+      <Metric label="Feed" value={feedLabel(feed)} />
       <Metric label="Price" value={metrics ? `$${format(metrics.price, 2)}` : '—'} />
       <Metric
         label="Change %"
@@ -3,6 +3,8 @@ import type {
   AudioFrame,
   AudioMetadata,
   ConnectionStatus,
+  FeedStatus,
+  FeedStatusMessage,
   MarketMetrics,
   MusicalParams,
 } from '../types';
@@ -19,6 +21,7 @@ export const useAudioStream = (
   const [status, setStatus] = useState<ConnectionStatus>('idle');
   const [latestMetrics, setLatestMetrics] = useState<MarketMetrics | null>(null);
   const [latestParams, setLatestParams] = useState<MusicalParams | null>(null);
+  const [feed, setFeed] = useState<FeedStatus | null>(null);
 
   const disconnect = useCallback(() => {
     if (wsRef.current) {
@@ -42,8 +45,14 @@ export const useAudioStream = (
     ws.onclose = () => setStatus('idle');
     ws.onmessage = (event) => {
       if (typeof event.data === 'string') {
-        const metadata = JSON.parse(event.data) as AudioMetadata;
+        const message = JSON.parse(event.data) as AudioMetadata | FeedStatusMessage;
+        if ('type' in message && message.type === 'feed_status') {
+          setFeed(message.feed);
+          return;
+        }
+        const metadata = message as AudioMetadata;
         lastMetadata.current = metadata;
+        setFeed(metadata.feed ?? null);
         setLatestMetrics(metadata.metrics);
         setLatestParams(metadata.params);
         return;
@@ -70,6 +79,7 @@ export const useAudioStream = (
     status,
     latestMetrics,
     latestParams,
+    feed,
     reconnect: connect,
     disconnect,
   };
@@ -17,6 +17,95 @@ export interface MarketMetrics {
   volatility: number;
   tempo_bias: number;
   last_updated: string;
+  book?: BookMetrics | null;
+  flow?: TradeFlowMetrics | null;
+  session?: MarketSession | null;
+  macd?: MacdMetrics | null;
+  bollinger?: BollingerMetrics | null;
+  ema?: EmaMetrics | null;
+  atr?: number | null;
+  vwap?: VwapMetrics | null;
+  values?: Record<string, number>;
+  regime?: MarketRegime | null;
+  normalized?: NormalizedMetrics | null;
+  timeframes?: TimeframeMetrics[];
+}
+
+export interface TimeframeMetrics {
+  timeframe: string;
+  bar_secs: number;
+  price_change_percent: number;
+  rsi: number;
+  volatility: number;
+  macd?: MacdMetrics | null;
+  ema?: EmaMetrics | null;
+  regime?: MarketRegime | null;
+}
+
+export interface NormalizedValue {
+  z_score: number;
+  percentile: number;
+}
+
+export interface NormalizedMetrics {
+  price: NormalizedValue;
+  price_change: NormalizedValue;
+  volatility: NormalizedValue;
+  volume_ratio: NormalizedValue;
+}
+
+export type MarketRegime =
+  | 'trending_up'
+  | 'trending_down'
+  | 'ranging'
+  | 'breakout'
+  | 'capitulation';
+
+export interface MacdMetrics {
+  line: number;
+  signal: number;
+  histogram: number;
+}
+
+export interface BollingerMetrics {
+  middle: number;
+  upper: number;
+  lower: number;
+  percent_b: number;
+  bandwidth: number;
+  squeeze: boolean;
+}
+
+export interface EmaMetrics {
+  fast: number;
+  slow: number;
+  cross?: 'bullish' | 'bearish' | null;
+}
+
+export interface VwapMetrics {
+  vwap: number;
+  deviation_pct: number;
+}
+
+export type MarketSession = 'pre_market' | 'regular' | 'post_market' | 'closed';
+
+export interface TradeFlowMetrics {
+  buy_volume: number;
+  sell_volume: number;
+  cumulative_delta: number;
+  imbalance: number;
+}
+
+export interface BookMetrics {
+  best_bid: number;
+  best_ask: number;
+  spread: number;
+  spread_bps: number;
+  imbalance: number;
+  weighted_mid: number;
+  bid_depth: number;
+  ask_depth: number;
+  liquidity_holes: number;
 }
 
 export interface MusicalParams {
@@ -28,8 +117,24 @@ export interface MusicalParams {
   distortion: number;
   volume_intensity: number;
   style: string;
+  pan?: number;
+  brightness?: number;
+  accents?: Accent[];
+  progression?: number[];
 }
 
+export type Accent = 'crash' | 'riser' | { stinger: { rising: boolean } };
+
+export type MarketEvent = { at: string; price: number } & (
+  | { type: 'session_high' }
+  | { type: 'session_low' }
+  | { type: 'round_number'; level: number; direction: 'bullish' | 'bearish' }
+  | { type: 'volume_spike'; volume: number; sigma: number }
+  | { type: 'rsi_cross'; level: number; direction: 'bullish' | 'bearish' }
+  | { type: 'large_trade'; volume: number; side?: 'buy' | 'sell' | null }
+  | { type: 'reconnect_gap'; gap_pct: number; gap_secs: number }
+);
+
 export interface AudioMetadata {
   asset: string;
   sample_rate: number;
@@ -38,7 +143,45 @@ export interface AudioMetadata {
   timestamp: string;
   metrics: MarketMetrics;
   params: MusicalParams;
+  events?: MarketEvent[];
   payload_bytes: number;
+  feed?: FeedStatus | null;
+}
+
+export type FeedState =
+  | 'connecting'
+  | 'connected'
+  | 'paused'
+  | 'disconnected'
+  | 'circuit_open';
+
+export interface RejectionCounts {
+  invalid: number;
+  outlier: number;
+  out_of_order: number;
+  duplicate: number;
+}
+
+export interface FeedStatus {
+  symbol: string;
+  source: string;
+  state: FeedState;
+  degraded: boolean;
+  last_tick: string | null;
+  last_tick_age_secs: number | null;
+  tick_rate: number;
+  reconnects: number;
+  errors: number;
+  last_error: string | null;
+  rejected: RejectionCounts;
+  dropped: number;
+  stale_after_secs: number;
+}
+
+/** Sent while no audio is flowing; audio metadata frames carry no `type`. */
+export interface FeedStatusMessage {
+  type: 'feed_status';
+  feed: FeedStatus;
 }
 
 export interface AudioFrame {
@@ -6,13 +6,15 @@ use axum::{
 };
 use serde::Serialize;
 
-use crate::state::AppState;
+use crate::{models::FeedStatus, state::AppState};
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/health", get(health))
         .route("/api/assets", get(list_assets))
         .route("/api/metrics/:symbol", get(latest_metrics))
+        .route("/api/feeds", get(list_feeds))
+        .route("/api/feeds/:symbol", get(feed_status))
 }
 
 async fn health() -> Json<HealthResponse> {
@@ -31,6 +33,7 @@ async fn list_assets(State(state): State<AppState>) -> Json<Vec<AssetSummary>> {
             display_name: asset.display_name.clone(),
             category: asset.category.as_str().to_string(),
             description: asset.description.clone(),
+            source: asset.source.name.clone(),
         })
         .collect();
     Json(assets)
@@ -46,6 +49,20 @@ async fn latest_metrics(
         .ok_or(StatusCode::NOT_FOUND)
 }
 
+async fn list_feeds(State(state): State<AppState>) -> Json<Vec<FeedStatus>> {
+    Json(state.feed_health().statuses())
+}
+
+async fn feed_status(
+    Path(symbol): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<FeedStatus>, StatusCode> {
+    state
+        .feed_status(&symbol)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
 #[derive(Serialize)]
 struct HealthResponse {
     status: &'static str,
@@ -58,4 +75,5 @@ struct AssetSummary {
     display_name: String,
     category: String,
     description: String,
+    source: String,
 }
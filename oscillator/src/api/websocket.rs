@@ -6,10 +6,20 @@ use axum::{
     response::IntoResponse,
 };
 use serde::Serialize;
-use tokio::sync::broadcast;
+use tokio::{
+    sync::broadcast,
+    time::{Duration, Instant, interval},
+};
 use tracing::{error, info, warn};
 
-use crate::{models::AudioPacket, state::AppState};
+use crate::{
+    models::{AudioPacket, FeedStatus},
+    state::AppState,
+};
+
+/// Without audio for this long, subscribers get a standalone feed status frame
+/// so a dead feed is reported rather than heard as silence.
+const STATUS_INTERVAL: Duration = Duration::from_secs(5);
 
 #[derive(Debug, serde::Deserialize)]
 pub struct AudioStreamQuery {
@@ -28,6 +38,8 @@ pub async fn upgrade(
 async fn handle_socket(mut socket: WebSocket, state: AppState, asset: String) {
     info!(%asset, "WebSocket subscriber connected");
     let mut stream = state.subscribe(&asset);
+    let mut status_timer = interval(STATUS_INTERVAL);
+    let mut last_sent = Instant::now() - STATUS_INTERVAL;
 
     loop {
         tokio::select! {
@@ -50,13 +62,26 @@ async fn handle_socket(mut socket: WebSocket, state: AppState, asset: String) {
                     _ => {}
                 }
             }
+            _ = status_timer.tick() => {
+                if last_sent.elapsed() < STATUS_INTERVAL {
+                    continue;
+                }
+                let Some(feed) = state.feed_status(&asset) else { continue };
+                if let Err(err) = send_status(&mut socket, feed).await {
+                    error!(%asset, %err, "WebSocket send error");
+                    break;
+                }
+                last_sent = Instant::now();
+            }
             packet = stream.recv() => {
                 match packet {
                     Ok(packet) => {
-                        if let Err(err) = send_packet(&mut socket, &packet).await {
+                        let feed = state.feed_status(&asset);
+                        if let Err(err) = send_packet(&mut socket, &packet, feed).await {
                             error!(%asset, %err, "WebSocket send error");
                             break;
                         }
+                        last_sent = Instant::now();
                     }
                     Err(broadcast::error::RecvError::Lagged(skipped)) => {
                         warn!(%asset, skipped, "WebSocket lagged");
@@ -71,7 +96,22 @@ async fn handle_socket(mut socket: WebSocket, state: AppState, asset: String) {
     info!(%asset, "WebSocket disconnected");
 }
 
-async fn send_packet(socket: &mut WebSocket, packet: &AudioPacket) -> anyhow::Result<()> {
+async fn send_status(socket: &mut WebSocket, feed: FeedStatus) -> anyhow::Result<()> {
+    let message = FeedStatusMessage {
+        kind: "feed_status",
+        feed,
+    };
+    socket
+        .send(Message::Text(serde_json::to_string(&message)?))
+        .await?;
+    Ok(())
+}
+
+async fn send_packet(
+    socket: &mut WebSocket,
+    packet: &AudioPacket,
+    feed: Option<FeedStatus>,
+) -> anyhow::Result<()> {
     let metadata = AudioMetadata {
         asset: &packet.asset,
         sample_rate: packet.chunk.sample_rate,
@@ -80,7 +120,9 @@ async fn send_packet(socket: &mut WebSocket, packet: &AudioPacket) -> anyhow::Re
         timestamp: packet.chunk.timestamp.to_rfc3339(),
         metrics: &packet.metrics,
         params: &packet.params,
+        events: &packet.events,
         payload_bytes: packet.chunk.samples.len(),
+        feed,
     };
 
     let meta_json = serde_json::to_string(&metadata)?;
@@ -100,5 +142,15 @@ struct AudioMetadata<'a> {
     timestamp: String,
     metrics: &'a crate::models::MarketMetrics,
     params: &'a crate::models::MusicalParams,
+    events: &'a [crate::models::MarketEvent],
     payload_bytes: usize,
+    feed: Option<FeedStatus>,
+}
+
+/// Sent between audio packets while none are flowing; audio metadata has no `type`.
+#[derive(Serialize)]
+struct FeedStatusMessage {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    feed: FeedStatus,
 }
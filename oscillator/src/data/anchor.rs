@@ -0,0 +1,222 @@
+use std::{collections::VecDeque, str::FromStr};
+
+use chrono::{DateTime, Days, Duration, NaiveTime, Utc};
+
+use crate::data::calendar::TradingCalendar;
+
+/// Rolling-window samples are kept at most this often, so a day of one-second
+/// ticks stays a few thousand entries.
+const SAMPLE_SPACING_SECS: i64 = 60;
+
+/// What `price_change_percent` is measured against. Selected per asset with
+/// the `change_anchor` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeAnchor {
+    /// The price `window` ago (`rolling_24h`, or `rolling:<hours>`); the usual
+    /// crypto convention.
+    Rolling(Duration),
+    /// The first price of the current regular session (`session`). Assets without
+    /// a trading calendar fall back to a UTC midnight reset.
+    SessionOpen,
+    /// The first price after a fixed daily UTC time (`utc`, or `utc:HH:MM`).
+    DailyUtc(NaiveTime),
+}
+
+impl Default for ChangeAnchor {
+    fn default() -> Self {
+        Self::Rolling(Duration::hours(24))
+    }
+}
+
+impl FromStr for ChangeAnchor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_ascii_lowercase();
+        let (kind, arg) = match lower.split_once(':') {
+            Some((kind, arg)) => (kind, Some(arg)),
+            None => (lower.as_str(), None),
+        };
+        match (kind, arg) {
+            ("rolling" | "rolling_24h", None) => Ok(Self::default()),
+            ("rolling", Some(hours)) => {
+                let hours: f64 = hours.parse()?;
+                anyhow::ensure!(hours > 0.0, "rolling window must be positive");
+                Ok(Self::Rolling(Duration::seconds((hours * 3600.0) as i64)))
+            }
+            ("session", None) => Ok(Self::SessionOpen),
+            ("utc", None) => Ok(Self::DailyUtc(NaiveTime::MIN)),
+            ("utc", Some(time)) => Ok(Self::DailyUtc(NaiveTime::parse_from_str(time, "%H:%M")?)),
+            _ => Err(anyhow::anyhow!("unknown change anchor {s}")),
+        }
+    }
+}
+
+/// Tracks the reference price for one asset's `ChangeAnchor`.
+///
+/// Everything runs on tick timestamps rather than the wall clock, so warm-up
+/// history and replays roll over at the same points as live data.
+#[derive(Debug, Clone)]
+pub struct PriceAnchor {
+    anchor: ChangeAnchor,
+    calendar: Option<TradingCalendar>,
+    /// `(time, price)` samples covering the rolling window, for `Rolling`.
+    samples: VecDeque<(DateTime<Utc>, f64)>,
+    /// Reset boundary the current reference belongs to, for the daily anchors.
+    period_start: Option<DateTime<Utc>>,
+    reference: Option<f64>,
+}
+
+impl PriceAnchor {
+    pub fn new(anchor: ChangeAnchor, calendar: Option<TradingCalendar>) -> Self {
+        Self {
+            anchor,
+            calendar,
+            samples: VecDeque::new(),
+            period_start: None,
+            reference: None,
+        }
+    }
+
+    pub fn push(&mut self, at: DateTime<Utc>, price: f64) {
+        match self.anchor {
+            ChangeAnchor::Rolling(window) => self.push_rolling(window, at, price),
+            ChangeAnchor::SessionOpen | ChangeAnchor::DailyUtc(_) => {
+                let boundary = self.boundary(at);
+                if self.reference.is_none() || boundary > self.period_start {
+                    self.period_start = boundary;
+                    self.reference = Some(price);
+                }
+            }
+        }
+    }
+
+    /// The price changes are currently measured against.
+    pub fn reference(&self) -> Option<f64> {
+        self.reference
+    }
+
+    pub fn change_pct(&self, price: f64) -> f64 {
+        match self.reference {
+            Some(reference) if reference > 0.0 => (price - reference) / reference * 100.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Until a full window has been seen the oldest sample stands in, i.e. the
+    /// change since the first price.
+    fn push_rolling(&mut self, window: Duration, at: DateTime<Utc>, price: f64) {
+        let due = self
+            .samples
+            .back()
+            .is_none_or(|&(last, _)| at - last >= Duration::seconds(SAMPLE_SPACING_SECS));
+        if due {
+            self.samples.push_back((at, price));
+        }
+        // Keep the newest sample at or before the cutoff: that is the price
+        // `window` ago.
+        let cutoff = at - window;
+        while self.samples.get(1).is_some_and(|&(time, _)| time <= cutoff) {
+            self.samples.pop_front();
+        }
+        self.reference = self.samples.front().map(|&(_, price)| price);
+    }
+
+    /// Latest reset point at or before `at` for the daily anchors.
+    fn boundary(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let reset = match self.anchor {
+            ChangeAnchor::SessionOpen => match &self.calendar {
+                Some(calendar) => return calendar.last_open(at),
+                None => NaiveTime::MIN,
+            },
+            ChangeAnchor::DailyUtc(reset) => reset,
+            ChangeAnchor::Rolling(_) => return None,
+        };
+        let today = at.date_naive().and_time(reset).and_utc();
+        if today <= at {
+            Some(today)
+        } else {
+            at.date_naive()
+                .checked_sub_days(Days::new(1))
+                .map(|day| day.and_time(reset).and_utc())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn parses_anchor_options() {
+        assert_eq!(
+            "rolling_24h".parse::<ChangeAnchor>().unwrap(),
+            ChangeAnchor::Rolling(Duration::hours(24))
+        );
+        assert_eq!(
+            "rolling:4".parse::<ChangeAnchor>().unwrap(),
+            ChangeAnchor::Rolling(Duration::hours(4))
+        );
+        assert_eq!(
+            "session".parse::<ChangeAnchor>().unwrap(),
+            ChangeAnchor::SessionOpen
+        );
+        assert_eq!(
+            "utc:08:30".parse::<ChangeAnchor>().unwrap(),
+            ChangeAnchor::DailyUtc(NaiveTime::from_hms_opt(8, 30, 0).unwrap())
+        );
+        assert!("weekly".parse::<ChangeAnchor>().is_err());
+    }
+
+    #[test]
+    fn rolling_anchor_tracks_the_price_a_window_ago() {
+        let mut anchor = PriceAnchor::new(ChangeAnchor::Rolling(Duration::hours(24)), None);
+        let start = at(2026, 3, 2, 0, 0);
+        for hour in 0..48 {
+            anchor.push(start + Duration::hours(hour), 100.0 + hour as f64);
+        }
+        // 47h in, the reference is the price at 23h.
+        assert_eq!(anchor.reference(), Some(123.0));
+        assert!((anchor.change_pct(147.0) - 24.0 / 123.0 * 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn daily_utc_anchor_resets_at_the_configured_time() {
+        let reset = NaiveTime::from_hms_opt(8, 0, 0).unwrap();
+        let mut anchor = PriceAnchor::new(ChangeAnchor::DailyUtc(reset), None);
+        anchor.push(at(2026, 3, 2, 6, 0), 90.0);
+        anchor.push(at(2026, 3, 2, 7, 59), 95.0);
+        assert_eq!(anchor.reference(), Some(90.0));
+        anchor.push(at(2026, 3, 2, 8, 5), 100.0);
+        anchor.push(at(2026, 3, 2, 20, 0), 110.0);
+        assert_eq!(anchor.reference(), Some(100.0));
+        // A gap spanning a reset still rolls over on the next tick.
+        anchor.push(at(2026, 3, 4, 9, 0), 120.0);
+        assert_eq!(anchor.reference(), Some(120.0));
+    }
+
+    #[test]
+    fn session_anchor_resets_at_the_regular_open() {
+        let mut anchor = PriceAnchor::new(
+            ChangeAnchor::SessionOpen,
+            Some(TradingCalendar::us_equities()),
+        );
+        // Friday 2026-03-06: 09:30 ET is 14:30 UTC.
+        anchor.push(at(2026, 3, 6, 14, 29), 99.0);
+        anchor.push(at(2026, 3, 6, 14, 30), 100.0);
+        anchor.push(at(2026, 3, 6, 20, 30), 104.0);
+        assert_eq!(anchor.reference(), Some(100.0));
+        // Monday's pre-market still measures against Friday's open.
+        anchor.push(at(2026, 3, 9, 12, 0), 103.0);
+        assert_eq!(anchor.reference(), Some(100.0));
+        // US daylight saving starts on 2026-03-08, so Monday opens at 13:30 UTC.
+        anchor.push(at(2026, 3, 9, 13, 31), 105.0);
+        assert_eq!(anchor.reference(), Some(105.0));
+    }
+}
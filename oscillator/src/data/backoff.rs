@@ -0,0 +1,292 @@
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::Deserialize;
+use tokio::time::{Instant, sleep};
+use tracing::{error, warn};
+
+/// Reconnect timing for one market source: exponential backoff with jitter,
+/// capped at `max_secs`, plus a circuit breaker that stops retrying for
+/// `open_secs` after `open_after` consecutive failures.
+///
+/// Deserializes per source name from the JSON file named by `OSC_RECONNECT`,
+/// e.g. `{"binance": {"initial_secs": 1, "max_secs": 60, "open_after": 8}}`;
+/// fields left out keep their defaults.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ReconnectPolicy {
+    pub initial_secs: f64,
+    pub max_secs: f64,
+    pub multiplier: f64,
+    /// Fraction of each delay that is randomized, so assets on a failed
+    /// exchange do not retry in lockstep. `0` disables jitter.
+    pub jitter: f64,
+    /// Consecutive failures that open the circuit; `0` never opens it.
+    pub open_after: u32,
+    pub open_secs: f64,
+    /// How long a connection must stay up without delivering data before a
+    /// drop no longer counts towards the circuit breaker.
+    pub healthy_secs: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_secs: 1.0,
+            max_secs: 60.0,
+            multiplier: 2.0,
+            jitter: 0.3,
+            open_after: 10,
+            open_secs: 300.0,
+            healthy_secs: 30.0,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    pub fn backoff(&self) -> Backoff {
+        Backoff::new(self.clone(), StdRng::from_entropy())
+    }
+
+    /// Same as `backoff` but with reproducible jitter.
+    pub fn seeded_backoff(&self, seed: u64) -> Backoff {
+        Backoff::new(self.clone(), StdRng::seed_from_u64(seed))
+    }
+}
+
+/// Retry state for one reconnect loop. Call `failure` (or `wait`) after each
+/// failed attempt, `connected` when a connection comes up and `delivered` when
+/// it yields data.
+///
+/// Connecting alone does not clear the failures: a venue that accepts
+/// connections and drops them straight away still opens the circuit. They are
+/// cleared by the first data frame, or by a drop after the connection stayed
+/// up for `healthy_secs`.
+#[derive(Debug)]
+pub struct Backoff {
+    policy: ReconnectPolicy,
+    rng: StdRng,
+    failures: u32,
+    connected_at: Option<Instant>,
+}
+
+impl Backoff {
+    fn new(policy: ReconnectPolicy, rng: StdRng) -> Self {
+        Self {
+            policy,
+            rng,
+            failures: 0,
+            connected_at: None,
+        }
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Whether the last failure tripped the circuit breaker.
+    pub fn is_open(&self) -> bool {
+        self.policy.open_after > 0 && self.failures >= self.policy.open_after
+    }
+
+    /// Records a failed attempt and returns how long to wait before the next.
+    ///
+    /// While the circuit is open every further failure waits the full
+    /// `open_secs`, so a half-open probe that fails re-opens it.
+    pub fn failure(&mut self) -> Duration {
+        let healthy = Duration::from_secs_f64(self.policy.healthy_secs.max(0.0));
+        if self
+            .connected_at
+            .take()
+            .is_some_and(|at| at.elapsed() >= healthy)
+        {
+            self.failures = 0;
+        }
+        self.failures = self.failures.saturating_add(1);
+        if self.is_open() {
+            return Duration::from_secs_f64(self.policy.open_secs.max(0.0));
+        }
+
+        let exponent = self.failures.saturating_sub(1).min(64) as i32;
+        let base = (self.policy.initial_secs * self.policy.multiplier.max(1.0).powi(exponent))
+            .min(self.policy.max_secs)
+            .max(0.0);
+        let jitter = self.policy.jitter.clamp(0.0, 1.0);
+        let delay = if jitter > 0.0 {
+            base * (1.0 - jitter * self.rng.r#gen::<f64>())
+        } else {
+            base
+        };
+        Duration::from_secs_f64(delay)
+    }
+
+    /// Records a failed attempt, hands the updated state to `on_failure` (e.g.
+    /// to report it as feed health) and sleeps for the resulting delay. The
+    /// log line is labelled with `feed` and escalates to an error when this
+    /// failure opened the circuit.
+    pub async fn wait(&mut self, feed: &str, on_failure: impl FnOnce(&Self)) -> Duration {
+        let delay = self.failure();
+        on_failure(self);
+        if self.failures == self.policy.open_after {
+            error!(
+                %feed,
+                failures = self.failures,
+                open_secs = delay.as_secs_f64(),
+                "Circuit breaker open - pausing reconnects"
+            );
+        } else {
+            warn!(
+                %feed,
+                failures = self.failures,
+                delay_secs = delay.as_secs_f64(),
+                "Backing off before reconnecting"
+            );
+        }
+        sleep(delay).await;
+        delay
+    }
+
+    /// Marks a new connection as up, on probation until it delivers data or
+    /// stays up for `healthy_secs`.
+    pub fn connected(&mut self) {
+        self.connected_at = Some(Instant::now());
+    }
+
+    /// Marks that the connection delivered data, which proves it healthy.
+    pub fn delivered(&mut self) {
+        if self.connected_at.take().is_some() {
+            self.reset();
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.failures = 0;
+        self.connected_at = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::time::Instant;
+
+    use super::*;
+
+    fn policy() -> ReconnectPolicy {
+        ReconnectPolicy {
+            initial_secs: 1.0,
+            max_secs: 8.0,
+            multiplier: 2.0,
+            jitter: 0.0,
+            open_after: 6,
+            open_secs: 120.0,
+            healthy_secs: 30.0,
+        }
+    }
+
+    #[test]
+    fn delays_grow_exponentially_up_to_the_cap() {
+        let mut backoff = policy().backoff();
+        let delays: Vec<f64> = (0..5).map(|_| backoff.failure().as_secs_f64()).collect();
+        assert_eq!(delays, [1.0, 2.0, 4.0, 8.0, 8.0]);
+        assert!(!backoff.is_open());
+    }
+
+    #[test]
+    fn circuit_opens_after_consecutive_failures_and_resets() {
+        let mut backoff = policy().backoff();
+        for _ in 0..5 {
+            backoff.failure();
+        }
+        assert_eq!(backoff.failure(), Duration::from_secs(120));
+        assert!(backoff.is_open());
+        // A failed half-open probe keeps the circuit open.
+        assert_eq!(backoff.failure(), Duration::from_secs(120));
+
+        backoff.reset();
+        assert!(!backoff.is_open());
+        assert_eq!(backoff.failure(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn jitter_stays_within_the_configured_fraction() {
+        let policy = ReconnectPolicy {
+            jitter: 0.5,
+            open_after: 0,
+            ..policy()
+        };
+        let mut a = policy.seeded_backoff(7);
+        let mut b = policy.seeded_backoff(8);
+        let mut differs = false;
+        for step in 0..20 {
+            let base = (2f64.powi(step)).min(8.0);
+            let (da, db) = (a.failure().as_secs_f64(), b.failure().as_secs_f64());
+            assert!((base * 0.5..=base).contains(&da), "{da} vs {base}");
+            differs |= da != db;
+        }
+        assert!(differs, "seeds should not retry in lockstep");
+        assert!(!a.is_open());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_sleeps_for_the_backoff_delay() {
+        let mut backoff = policy().backoff();
+        let start = Instant::now();
+        for _ in 0..3 {
+            backoff.wait("test", |_| {}).await;
+        }
+        assert_eq!(start.elapsed(), Duration::from_secs(7));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn flapping_connections_still_open_the_circuit() {
+        let mut backoff = policy().backoff();
+        // Every connection comes up and drops before delivering anything.
+        for _ in 0..6 {
+            backoff.connected();
+            tokio::time::advance(Duration::from_secs(5)).await;
+            backoff.wait("test", |_| {}).await;
+        }
+        assert!(backoff.is_open());
+        assert_eq!(backoff.failures(), 6);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn data_or_a_long_connection_clears_the_failures() {
+        let mut backoff = policy().backoff();
+        for _ in 0..3 {
+            backoff.failure();
+        }
+        backoff.connected();
+        backoff.delivered();
+        assert_eq!(backoff.failures(), 0);
+        // Only the first frame of a connection counts.
+        backoff.failure();
+        backoff.delivered();
+        assert_eq!(backoff.failures(), 1);
+
+        backoff.connected();
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert_eq!(backoff.failure(), Duration::from_secs(1));
+        assert_eq!(backoff.failures(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_reports_the_failure_before_sleeping() {
+        let mut backoff = ReconnectPolicy {
+            open_after: 2,
+            ..policy()
+        }
+        .backoff();
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            let start = Instant::now();
+            backoff
+                .wait("test", |backoff| {
+                    seen.push((backoff.failures(), backoff.is_open()));
+                    assert_eq!(start.elapsed(), Duration::ZERO);
+                })
+                .await;
+        }
+        assert_eq!(seen, [(1, false), (2, true), (3, true)]);
+    }
+}
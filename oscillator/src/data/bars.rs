@@ -0,0 +1,154 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+use crate::models::{Bar, PriceTick, TradeSide};
+
+/// Aggregates ticks into OHLCV bars of a fixed duration aligned to the epoch.
+///
+/// A bar closes when the first tick of a later interval arrives, so intervals
+/// without any ticks produce no bar rather than a flat placeholder.
+#[derive(Debug, Clone)]
+pub struct BarBuilder {
+    duration: Duration,
+    current: Option<Bar>,
+}
+
+impl BarBuilder {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration: duration.max(Duration::milliseconds(1)),
+            current: None,
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// The bar still collecting ticks, if any.
+    pub fn current(&self) -> Option<&Bar> {
+        self.current.as_ref()
+    }
+
+    /// Adds `tick` to its interval and returns the bar it closed, if any. Ticks
+    /// older than the forming bar are folded into it rather than reopening a
+    /// closed interval.
+    pub fn push(&mut self, tick: &PriceTick) -> Option<Bar> {
+        let start = self.interval_start(tick.timestamp);
+        let closed = match &self.current {
+            Some(bar) if start > bar.start => self.current.take(),
+            _ => None,
+        };
+
+        let bar = self.current.get_or_insert_with(|| Bar {
+            symbol: tick.symbol.clone(),
+            start,
+            end: start + self.duration,
+            open: tick.price,
+            high: tick.price,
+            low: tick.price,
+            close: tick.price,
+            volume: 0.0,
+            buy_volume: 0.0,
+            sell_volume: 0.0,
+            trades: 0,
+        });
+        bar.high = bar.high.max(tick.price);
+        bar.low = bar.low.min(tick.price);
+        bar.close = tick.price;
+        bar.volume += tick.volume;
+        match tick.side {
+            Some(TradeSide::Buy) => bar.buy_volume += tick.volume,
+            Some(TradeSide::Sell) => bar.sell_volume += tick.volume,
+            None => {}
+        }
+        bar.trades += 1;
+
+        closed
+    }
+
+    fn interval_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let step = self.duration.num_milliseconds();
+        let millis = at.timestamp_millis();
+        Utc.timestamp_millis_opt(millis - millis.rem_euclid(step))
+            .single()
+            .unwrap_or(at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(millis: i64, price: f64, volume: f64, side: Option<TradeSide>) -> PriceTick {
+        PriceTick {
+            symbol: "btcusdt".to_string(),
+            price,
+            volume,
+            timestamp: Utc
+                .timestamp_millis_opt(1_700_000_000_000 + millis)
+                .unwrap(),
+            side,
+        }
+    }
+
+    #[test]
+    fn aggregates_ohlcv_within_an_interval() {
+        let mut builder = BarBuilder::new(Duration::seconds(5));
+        assert!(
+            builder
+                .push(&tick(0, 100.0, 1.0, Some(TradeSide::Buy)))
+                .is_none()
+        );
+        assert!(
+            builder
+                .push(&tick(1_000, 103.0, 2.0, Some(TradeSide::Sell)))
+                .is_none()
+        );
+        assert!(builder.push(&tick(2_000, 99.0, 0.5, None)).is_none());
+        assert!(
+            builder
+                .push(&tick(4_999, 101.0, 1.5, Some(TradeSide::Buy)))
+                .is_none()
+        );
+
+        let bar = builder
+            .push(&tick(5_000, 102.0, 1.0, None))
+            .expect("closed bar");
+        assert_eq!(
+            (bar.open, bar.high, bar.low, bar.close),
+            (100.0, 103.0, 99.0, 101.0)
+        );
+        assert_eq!(bar.volume, 5.0);
+        assert_eq!((bar.buy_volume, bar.sell_volume), (2.5, 2.0));
+        assert_eq!(bar.trades, 4);
+        assert_eq!(bar.end - bar.start, Duration::seconds(5));
+
+        let forming = builder.current().expect("forming bar");
+        assert_eq!(forming.start, bar.end);
+        assert_eq!(forming.open, 102.0);
+    }
+
+    #[test]
+    fn bars_align_to_the_epoch_and_skip_empty_intervals() {
+        let mut builder = BarBuilder::new(Duration::minutes(1));
+        builder.push(&tick(0, 100.0, 1.0, None));
+        let bar = builder.push(&tick(600_000, 110.0, 1.0, None)).unwrap();
+        assert_eq!(bar.start.timestamp() % 60, 0);
+        assert_eq!(
+            builder.current().unwrap().start - bar.start,
+            Duration::minutes(10)
+        );
+    }
+
+    #[test]
+    fn late_ticks_fold_into_the_forming_bar() {
+        let mut builder = BarBuilder::new(Duration::seconds(1));
+        builder.push(&tick(1_500, 100.0, 1.0, None));
+        assert!(builder.push(&tick(200, 90.0, 1.0, None)).is_none());
+        let current = builder.current().unwrap();
+        assert_eq!(
+            (current.low, current.close, current.trades),
+            (90.0, 90.0, 2)
+        );
+    }
+}
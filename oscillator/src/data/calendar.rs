@@ -0,0 +1,294 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::models::MarketSession;
+
+/// NYSE/NASDAQ full-day closures. Extend this table (or ship an `OSC_CALENDAR`
+/// file) before the last listed year runs out.
+const US_HOLIDAYS: [(i32, u32, u32); 31] = [
+    (2025, 1, 1),
+    (2025, 1, 9),
+    (2025, 1, 20),
+    (2025, 2, 17),
+    (2025, 4, 18),
+    (2025, 5, 26),
+    (2025, 6, 19),
+    (2025, 7, 4),
+    (2025, 9, 1),
+    (2025, 11, 27),
+    (2025, 12, 25),
+    (2026, 1, 1),
+    (2026, 1, 19),
+    (2026, 2, 16),
+    (2026, 4, 3),
+    (2026, 5, 25),
+    (2026, 6, 19),
+    (2026, 7, 3),
+    (2026, 9, 7),
+    (2026, 11, 26),
+    (2026, 12, 25),
+    (2027, 1, 1),
+    (2027, 1, 18),
+    (2027, 2, 15),
+    (2027, 3, 26),
+    (2027, 5, 31),
+    (2027, 6, 18),
+    (2027, 7, 5),
+    (2027, 9, 6),
+    (2027, 11, 25),
+    (2027, 12, 24),
+];
+
+/// Set once a date past the holiday table has been warned about.
+static BEYOND_TABLE_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// NYSE/NASDAQ 13:00 early closes.
+const US_EARLY_CLOSES: [(i32, u32, u32); 6] = [
+    (2025, 7, 3),
+    (2025, 11, 28),
+    (2025, 12, 24),
+    (2026, 11, 27),
+    (2026, 12, 24),
+    (2027, 11, 26),
+];
+
+/// Exchange session hours in the exchange's local time zone plus its holiday table.
+///
+/// Deserializes from the JSON file named by `OSC_CALENDAR`; fields left out fall
+/// back to the built-in NYSE/NASDAQ schedule, e.g.
+/// `{"timezone": "America/New_York", "holidays": ["2026-12-25"], "early_closes": {"2026-12-24": "13:00"}}`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TradingCalendar {
+    pub timezone: Tz,
+    pub pre_market: NaiveTime,
+    pub open: NaiveTime,
+    pub close: NaiveTime,
+    pub post_market: NaiveTime,
+    pub holidays: BTreeSet<NaiveDate>,
+    /// Half days; post-market keeps its usual length after the early close.
+    pub early_closes: BTreeMap<NaiveDate, NaiveTime>,
+}
+
+impl Default for TradingCalendar {
+    fn default() -> Self {
+        Self::us_equities()
+    }
+}
+
+impl TradingCalendar {
+    /// NYSE/NASDAQ: pre-market from 04:00, regular 09:30-16:00, post-market until 20:00 ET.
+    pub fn us_equities() -> Self {
+        let date = |(y, m, d): (i32, u32, u32)| NaiveDate::from_ymd_opt(y, m, d).expect("date");
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).expect("time");
+        Self {
+            timezone: chrono_tz::America::New_York,
+            pre_market: time(4, 0),
+            open: time(9, 30),
+            close: time(16, 0),
+            post_market: time(20, 0),
+            holidays: US_HOLIDAYS.into_iter().map(date).collect(),
+            early_closes: US_EARLY_CLOSES
+                .into_iter()
+                .map(|day| (date(day), time(13, 0)))
+                .collect(),
+        }
+    }
+
+    /// Whether the holiday table lists `date`'s year. Later dates are treated
+    /// as ordinary weekdays, so holidays there read as trading days.
+    pub fn covers(&self, date: NaiveDate) -> bool {
+        self.holidays
+            .last()
+            .is_some_and(|last| date.year() <= last.year())
+    }
+
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        if !self.covers(date) && !BEYOND_TABLE_WARNED.swap(true, Ordering::Relaxed) {
+            warn!(
+                %date,
+                "Trading calendar has no holidays listed this far ahead - holidays will be traded through"
+            );
+        }
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains(&date)
+    }
+
+    pub fn session_at(&self, now: DateTime<Utc>) -> MarketSession {
+        let local = now.with_timezone(&self.timezone);
+        let date = local.date_naive();
+        if !self.is_trading_day(date) {
+            return MarketSession::Closed;
+        }
+
+        let time = local.time();
+        let (close, post_market) = self.closing_times(date);
+        if time < self.pre_market {
+            MarketSession::Closed
+        } else if time < self.open {
+            MarketSession::PreMarket
+        } else if time < close {
+            MarketSession::Regular
+        } else if time < post_market {
+            MarketSession::PostMarket
+        } else {
+            MarketSession::Closed
+        }
+    }
+
+    /// Start of the next pre-market session after `now`.
+    pub fn next_open(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let local = now.with_timezone(&self.timezone);
+        let mut date = local.date_naive();
+        if local.time() >= self.pre_market {
+            date = date + Days::new(1);
+        }
+
+        // Bounded so a misconfigured holiday table cannot spin forever.
+        for _ in 0..366 {
+            if self.is_trading_day(date)
+                && let Some(start) = self
+                    .timezone
+                    .from_local_datetime(&date.and_time(self.pre_market))
+                    .earliest()
+            {
+                return start.with_timezone(&Utc);
+            }
+            date = date + Days::new(1);
+        }
+        now + chrono::Duration::days(1)
+    }
+
+    /// Start of the latest regular session at or before `now`.
+    pub fn last_open(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut date = now.with_timezone(&self.timezone).date_naive();
+        // Bounded like `next_open`.
+        for _ in 0..366 {
+            if self.is_trading_day(date)
+                && let Some(open) = self
+                    .timezone
+                    .from_local_datetime(&date.and_time(self.open))
+                    .earliest()
+                && open <= now
+            {
+                return Some(open.with_timezone(&Utc));
+            }
+            date = date - Days::new(1);
+        }
+        None
+    }
+
+    fn closing_times(&self, date: NaiveDate) -> (NaiveTime, NaiveTime) {
+        match self.early_closes.get(&date) {
+            Some(early) => (*early, *early + (self.post_market - self.close)),
+            None => (self.close, self.post_market),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(raw: &str) -> DateTime<Utc> {
+        raw.parse().expect("timestamp")
+    }
+
+    #[test]
+    fn sessions_follow_new_york_across_the_dst_switch() {
+        let calendar = TradingCalendar::us_equities();
+        // 13:30 UTC is 08:30 EST on Friday but 09:30 EDT after the March 8 switch.
+        assert_eq!(
+            calendar.session_at(utc("2026-03-06T13:30:00Z")),
+            MarketSession::PreMarket
+        );
+        assert_eq!(
+            calendar.session_at(utc("2026-03-09T13:30:00Z")),
+            MarketSession::Regular
+        );
+        assert_eq!(
+            calendar.session_at(utc("2026-03-09T20:00:00Z")),
+            MarketSession::PostMarket
+        );
+        assert_eq!(
+            calendar.session_at(utc("2026-03-09T07:59:00Z")),
+            MarketSession::Closed
+        );
+    }
+
+    #[test]
+    fn next_open_skips_the_weekend_into_daylight_time() {
+        let calendar = TradingCalendar::us_equities();
+        // Friday post-market, then Saturday: both roll to Monday 04:00 EDT.
+        for now in ["2026-03-06T21:00:00Z", "2026-03-07T12:00:00Z"] {
+            assert_eq!(
+                calendar.next_open(utc(now)),
+                utc("2026-03-09T08:00:00Z"),
+                "{now}"
+            );
+        }
+        assert_eq!(
+            calendar.session_at(utc("2026-03-07T15:00:00Z")),
+            MarketSession::Closed
+        );
+        // Before pre-market the same day's session is next.
+        assert_eq!(
+            calendar.next_open(utc("2026-03-09T07:00:00Z")),
+            utc("2026-03-09T08:00:00Z")
+        );
+    }
+
+    #[test]
+    fn early_close_shortens_regular_and_post_market() {
+        let calendar = TradingCalendar::us_equities();
+        // Friday after Thanksgiving closes at 13:00 EST (18:00 UTC).
+        assert_eq!(
+            calendar.session_at(utc("2026-11-27T17:59:00Z")),
+            MarketSession::Regular
+        );
+        assert_eq!(
+            calendar.session_at(utc("2026-11-27T18:00:00Z")),
+            MarketSession::PostMarket
+        );
+        assert_eq!(
+            calendar.session_at(utc("2026-11-27T22:00:00Z")),
+            MarketSession::Closed
+        );
+    }
+
+    #[test]
+    fn holidays_are_closed_and_skipped() {
+        let calendar = TradingCalendar::us_equities();
+        assert_eq!(
+            calendar.session_at(utc("2026-11-26T15:00:00Z")),
+            MarketSession::Closed
+        );
+        // Wednesday after pre-market opened: Thanksgiving is skipped.
+        assert_eq!(
+            calendar.next_open(utc("2026-11-25T21:00:00Z")),
+            utc("2026-11-27T09:00:00Z")
+        );
+        assert_eq!(
+            calendar.last_open(utc("2026-11-26T15:00:00Z")),
+            Some(utc("2026-11-25T14:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn coverage_ends_with_the_holiday_table() {
+        let calendar = TradingCalendar::us_equities();
+        assert!(calendar.covers(NaiveDate::from_ymd_opt(2027, 12, 31).unwrap()));
+        assert!(!calendar.covers(NaiveDate::from_ymd_opt(2028, 1, 3).unwrap()));
+        let empty = TradingCalendar {
+            holidays: BTreeSet::new(),
+            ..TradingCalendar::us_equities()
+        };
+        assert!(!empty.covers(NaiveDate::from_ymd_opt(2026, 1, 2).unwrap()));
+    }
+}
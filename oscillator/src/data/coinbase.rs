@@ -0,0 +1,252 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::{sync::mpsc::Sender, time::timeout};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{error, info, warn};
+
+use crate::{
+    data::{
+        backoff::{Backoff, ReconnectPolicy},
+        crypto::{CONNECTION_TIMEOUT, HEARTBEAT_TIMEOUT},
+        health::FeedHealthStore,
+        source::MarketSource,
+    },
+    models::{AssetDescriptor, FeedState, PriceTick, TradeSide},
+};
+
+/// Coinbase Exchange trades from the public `matches` channel, one connection per asset.
+///
+/// The `heartbeat` channel is subscribed alongside so quiet products still
+/// produce traffic within the heartbeat timeout.
+///
+/// Source options:
+/// - `product`: Coinbase product id, defaults to the upper-cased asset symbol (e.g. `BTC-USD`)
+#[derive(Debug, Clone)]
+pub struct CoinbaseClient {
+    endpoint: String,
+    health: FeedHealthStore,
+    reconnect: ReconnectPolicy,
+}
+
+impl CoinbaseClient {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            health: FeedHealthStore::new(),
+            reconnect: ReconnectPolicy::default(),
+        }
+    }
+
+    /// Reports connection state, reconnects and errors to `health`.
+    pub fn with_health(mut self, health: FeedHealthStore) -> Self {
+        self.health = health;
+        self
+    }
+
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    /// Streams matches for one connection. Returns `Ok(())` once the receiver is gone.
+    async fn stream_matches(
+        &self,
+        product: &str,
+        symbol: &str,
+        tx: &Sender<PriceTick>,
+        backoff: &mut Backoff,
+    ) -> anyhow::Result<()> {
+        info!(%product, endpoint = %self.endpoint, "Connecting to Coinbase");
+        let (ws_stream, _) = timeout(CONNECTION_TIMEOUT, connect_async(&self.endpoint)).await??;
+        let (mut sink, mut stream) = ws_stream.split();
+
+        let subscribe = json!({
+            "type": "subscribe",
+            "product_ids": [product],
+            "channels": ["matches", "heartbeat"],
+        });
+        sink.send(Message::Text(subscribe.to_string())).await?;
+        self.health
+            .set_state(self.name(), symbol, FeedState::Connected);
+        backoff.connected();
+
+        loop {
+            tokio::select! {
+                _ = tx.closed() => return Ok(()),
+                message = timeout(HEARTBEAT_TIMEOUT, stream.next()) => {
+                    let Some(message) = message? else {
+                        anyhow::bail!("Coinbase closed the connection");
+                    };
+                    let message = message?;
+                    if !message.is_text() {
+                        continue;
+                    }
+                    let Some(tick) = parse_message(message.to_text()?, symbol)? else {
+                        continue;
+                    };
+                    backoff.delivered();
+                    if tx.send(tick).await.is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl MarketSource for CoinbaseClient {
+    fn name(&self) -> &str {
+        "coinbase"
+    }
+
+    async fn run(&self, asset: AssetDescriptor, tx: Sender<PriceTick>) -> anyhow::Result<()> {
+        let product = asset
+            .source
+            .option("product")
+            .map(str::to_string)
+            .unwrap_or_else(|| asset.symbol.to_uppercase());
+
+        let mut backoff = self.reconnect.backoff();
+        loop {
+            match self
+                .stream_matches(&product, &asset.symbol, &tx, &mut backoff)
+                .await
+            {
+                Ok(()) => return Ok(()),
+                Err(err) => {
+                    error!(%product, %err, "Coinbase stream failed");
+                    self.health.record_error(self.name(), &asset.symbol, &err);
+                }
+            }
+            if tx.is_closed() {
+                return Ok(());
+            }
+            backoff
+                .wait(&format!("Coinbase {product}"), |backoff| {
+                    self.health
+                        .record_backoff(self.name(), &asset.symbol, backoff)
+                })
+                .await;
+            self.health.record_reconnect(self.name(), &asset.symbol);
+        }
+    }
+}
+
+/// Turns one feed message into a tick for `symbol`. Control frames and matches
+/// without a usable price yield `None`; `error` frames fail the connection.
+pub fn parse_message(raw: &str, symbol: &str) -> anyhow::Result<Option<PriceTick>> {
+    match serde_json::from_str(raw)? {
+        FeedMessage::Match(trade) => {
+            let tick = trade.to_tick(symbol);
+            if tick.is_none() {
+                warn!(price = %trade.price, "Unreadable Coinbase match price - skipping");
+            }
+            Ok(tick)
+        }
+        FeedMessage::Error { message, reason } => match reason {
+            Some(reason) => anyhow::bail!("Coinbase error: {message} ({reason})"),
+            None => anyhow::bail!("Coinbase error: {message}"),
+        },
+        FeedMessage::Other => Ok(None),
+    }
+}
+
+/// Messages on the feed; `last_match`, heartbeats and subscription acks all land in `Other`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum FeedMessage {
+    Match(CoinbaseMatch),
+    Error {
+        message: String,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinbaseMatch {
+    price: String,
+    size: String,
+    /// Side of the resting maker order; the taker traded the other way.
+    side: MakerSide,
+    time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum MakerSide {
+    Buy,
+    Sell,
+}
+
+impl CoinbaseMatch {
+    fn to_tick(&self, symbol: &str) -> Option<PriceTick> {
+        let price = self
+            .price
+            .parse::<f64>()
+            .ok()
+            .filter(|price| price.is_finite() && *price > 0.0)?;
+        Some(PriceTick {
+            symbol: symbol.to_string(),
+            price,
+            volume: self.size.parse::<f64>().unwrap_or(0.0),
+            timestamp: self.time,
+            side: Some(match self.side {
+                MakerSide::Buy => TradeSide::Sell,
+                MakerSide::Sell => TradeSide::Buy,
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MATCH: &str = include_str!("../../tests/fixtures/coinbase/match.json");
+    const LAST_MATCH: &str = include_str!("../../tests/fixtures/coinbase/last_match.json");
+    const HEARTBEAT: &str = include_str!("../../tests/fixtures/coinbase/heartbeat.json");
+    const SUBSCRIPTIONS: &str = include_str!("../../tests/fixtures/coinbase/subscriptions.json");
+    const ERROR: &str = include_str!("../../tests/fixtures/coinbase/error.json");
+
+    #[test]
+    fn match_becomes_tick_with_taker_side() {
+        let tick = parse_message(MATCH, "btc-usd").unwrap().expect("tick");
+        assert_eq!(tick.symbol, "btc-usd");
+        assert_eq!(tick.price, 64_012.55);
+        assert_eq!(tick.volume, 0.0125);
+        assert_eq!(
+            tick.timestamp.to_rfc3339(),
+            "2024-05-01T12:00:01.123456+00:00"
+        );
+        // The maker sold, so the aggressor bought.
+        assert_eq!(tick.side, Some(TradeSide::Buy));
+    }
+
+    #[test]
+    fn control_messages_yield_no_tick() {
+        for raw in [LAST_MATCH, HEARTBEAT, SUBSCRIPTIONS] {
+            assert!(parse_message(raw, "btc-usd").unwrap().is_none(), "{raw}");
+        }
+    }
+
+    #[test]
+    fn matches_without_a_price_are_skipped() {
+        for price in ["", "abc", "0", "NaN"] {
+            let raw = MATCH.replace("\"64012.55\"", &format!("\"{price}\""));
+            assert!(parse_message(&raw, "btc-usd").unwrap().is_none(), "{price}");
+        }
+    }
+
+    #[test]
+    fn error_frames_fail_the_connection() {
+        let err = parse_message(ERROR, "btc-usd").unwrap_err();
+        assert!(err.to_string().contains("Failed to subscribe"), "{err}");
+    }
+}
@@ -1,96 +1,944 @@
-use std::time::Duration;
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
 
-use chrono::{TimeZone, Utc};
-use futures::StreamExt;
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use futures::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use reqwest::Client;
 use serde::Deserialize;
+use serde_json::json;
 use tokio::{
-    sync::mpsc::Sender,
-    task::JoinHandle,
-    time::{sleep, timeout},
+    sync::mpsc::{self, Receiver, Sender, UnboundedReceiver, UnboundedSender, error::TrySendError},
+    task::{AbortHandle, JoinHandle},
+    time::{self, sleep_until, timeout},
 };
-use tokio_tungstenite::connect_async;
-use tracing::{error, info, warn};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, error, info, warn};
 
-use crate::models::PriceTick;
+use crate::{
+    data::{
+        backoff::{Backoff, ReconnectPolicy},
+        health::FeedHealthStore,
+        orderbook::{OrderBook, OrderBookStore, Side},
+        source::{HistoryRequest, MarketSource},
+    },
+    models::{AssetDescriptor, FeedState, PriceTick, TradeSide},
+};
 
-const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
-const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(45);
+const SOURCE: &str = "binance";
+pub(crate) const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+pub(crate) const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(45);
+const DEFAULT_DEPTH_LEVELS: usize = 10;
+const SNAPSHOT_LIMIT: usize = 1000;
+/// Spacing between SUBSCRIBE/UNSUBSCRIBE requests. Binance drops connections
+/// sending more than 5 messages a second, pong replies included.
+const REQUEST_INTERVAL: Duration = Duration::from_millis(250);
+/// Minimum time between warnings about events dropped for a backlogged subscriber.
+const DROP_WARN_INTERVAL: Duration = Duration::from_secs(10);
+/// Most klines Binance returns per request.
+const KLINE_LIMIT: usize = 1000;
+/// Kline intervals Binance serves, with their length in seconds, shortest first.
+const KLINE_INTERVALS: [(&str, i64); 15] = [
+    ("1s", 1),
+    ("1m", 60),
+    ("3m", 180),
+    ("5m", 300),
+    ("15m", 900),
+    ("30m", 1_800),
+    ("1h", 3_600),
+    ("2h", 7_200),
+    ("4h", 14_400),
+    ("6h", 21_600),
+    ("8h", 28_800),
+    ("12h", 43_200),
+    ("1d", 86_400),
+    ("3d", 259_200),
+    ("1w", 604_800),
+];
 
+/// Binance market data multiplexed over a single combined-stream connection.
+///
+/// The connection is opened lazily on the first subscription and carries every
+/// subscribed stream; streams added or removed while it is live are sent as
+/// `SUBSCRIBE`/`UNSUBSCRIBE` requests instead of reconnecting.
+///
+/// Assets with the `depth` source option also get a local order book kept in
+/// sync from `@depth` diffs plus a REST snapshot, summarized into the
+/// `OrderBookStore` (top `depth_levels` levels, default 10).
 #[derive(Debug, Clone)]
 pub struct BinanceClient {
     endpoint: String,
+    rest_base: String,
+    http: Client,
+    books: Option<OrderBookStore>,
+    health: FeedHealthStore,
+    reconnect: ReconnectPolicy,
+    commands: Arc<OnceLock<UnboundedSender<HubCommand>>>,
+    /// Running order book tasks by symbol, with the store each publishes to.
+    order_books: Arc<Mutex<HashMap<String, (AbortHandle, OrderBookStore)>>>,
+}
+
+#[derive(Debug)]
+enum HubCommand {
+    Subscribe {
+        stream: String,
+        subscriber: Subscriber,
+    },
+    Unsubscribe {
+        stream: String,
+    },
 }
 
 impl BinanceClient {
     pub fn new(endpoint: impl Into<String>) -> Self {
         Self {
             endpoint: endpoint.into(),
+            rest_base: "https://api.binance.com".to_string(),
+            http: Client::builder()
+                .user_agent("oscillator/0.1")
+                .build()
+                .expect("reqwest client"),
+            books: None,
+            health: FeedHealthStore::new(),
+            reconnect: ReconnectPolicy::default(),
+            commands: Arc::new(OnceLock::new()),
+            order_books: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Reports connection state, reconnects and errors for subscribed symbols to `health`.
+    pub fn with_health(mut self, health: FeedHealthStore) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// Backoff for the shared combined-stream connection and depth snapshot retries.
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    /// REST API used for warm-up klines and depth snapshots.
+    pub fn with_rest_base(mut self, rest_base: impl Into<String>) -> Self {
+        self.rest_base = rest_base.into();
+        self
+    }
+
+    /// Enables order book ingestion for assets that ask for it, publishing
+    /// book summaries into `store`.
+    pub fn with_order_books(mut self, store: OrderBookStore) -> Self {
+        self.books = Some(store);
+        self
+    }
+
     pub fn spawn_trade_stream(
         &self,
         symbol: impl Into<String>,
         tx: Sender<PriceTick>,
     ) -> JoinHandle<()> {
         let symbol = symbol.into();
-        let endpoint = self.endpoint.clone();
-        tokio::spawn(async move {
-            loop {
-                if let Err(err) = Self::run_stream(&endpoint, &symbol, &tx).await {
-                    error!(%symbol, %err, "Binance stream failed");
+        let client = self.clone();
+        tokio::spawn(async move { client.stream_trades(&symbol, tx).await })
+    }
+
+    /// Routes trades for `symbol` to `tx` until the receiver is dropped or
+    /// `unsubscribe` is called.
+    pub fn subscribe(&self, symbol: impl Into<String>, tx: Sender<PriceTick>) {
+        let symbol = symbol.into();
+        self.send_command(HubCommand::Subscribe {
+            stream: stream_name(&symbol, StreamKind::Trade),
+            subscriber: Subscriber::new(symbol, StreamSink::Trades(tx)),
+        });
+    }
+
+    /// Stops the trade and depth streams for `symbol`, along with its order
+    /// book task if one is running.
+    pub fn unsubscribe(&self, symbol: impl Into<String>) {
+        let symbol = symbol.into();
+        self.stop_order_book(&symbol);
+        for kind in [StreamKind::Trade, StreamKind::Depth] {
+            self.send_command(HubCommand::Unsubscribe {
+                stream: stream_name(&symbol, kind),
+            });
+        }
+    }
+
+    /// Keeps a local order book for `symbol` in sync and publishes its summary
+    /// to `store` until the returned task is aborted or `symbol` is
+    /// unsubscribed. Replaces any order book task already running for `symbol`.
+    pub fn spawn_order_book(
+        &self,
+        symbol: impl Into<String>,
+        levels: usize,
+        store: OrderBookStore,
+    ) -> JoinHandle<()> {
+        let symbol = symbol.into();
+        let (tx, rx) = mpsc::channel(1024);
+        self.send_command(HubCommand::Subscribe {
+            stream: stream_name(&symbol, StreamKind::Depth),
+            subscriber: Subscriber::new(symbol.clone(), StreamSink::Depth(tx)),
+        });
+
+        let sync = DepthSync {
+            rest_base: self.rest_base.clone(),
+            http: self.http.clone(),
+            reconnect: self.reconnect.clone(),
+            symbol: symbol.clone(),
+            levels,
+            store: store.clone(),
+        };
+        let task = tokio::spawn(sync.run(rx));
+        let previous = self
+            .order_books
+            .lock()
+            .insert(symbol, (task.abort_handle(), store));
+        if let Some((previous, _)) = previous {
+            previous.abort();
+        }
+        task
+    }
+
+    /// Aborts the order book task for `symbol` and clears its summary.
+    fn stop_order_book(&self, symbol: &str) {
+        let Some((task, store)) = self.order_books.lock().remove(symbol) else {
+            return;
+        };
+        task.abort();
+        store.remove(symbol);
+    }
+
+    /// Up to `limit` closed klines of `interval` opening before `end` (or now),
+    /// oldest first.
+    async fn fetch_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        limit: usize,
+        end: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Vec<Kline>> {
+        // One extra row because the newest kline may still be open.
+        let mut url = format!(
+            "{}/api/v3/klines?symbol={}&interval={}&limit={}",
+            self.rest_base.trim_end_matches('/'),
+            symbol.to_uppercase(),
+            interval,
+            (limit + 1).min(KLINE_LIMIT)
+        );
+        if let Some(end) = end {
+            url.push_str(&format!("&endTime={}", end.timestamp_millis() - 1));
+        }
+        info!(%symbol, interval, limit, "Fetching Binance klines");
+        let rows: Vec<Vec<serde_json::Value>> = self
+            .http
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let end = end.unwrap_or_else(Utc::now);
+        let mut klines: Vec<Kline> = rows
+            .iter()
+            .filter_map(|row| Kline::from_row(row))
+            .filter(|kline| kline.close_time < end)
+            .collect();
+        let skip = klines.len().saturating_sub(limit);
+        Ok(klines.split_off(skip))
+    }
+
+    async fn stream_trades(&self, symbol: &str, tx: Sender<PriceTick>) {
+        self.subscribe(symbol, tx.clone());
+        // The hub prunes closed senders on its own; just hold the feed open.
+        tx.closed().await;
+    }
+
+    fn send_command(&self, command: HubCommand) {
+        let commands = self.commands.get_or_init(|| {
+            let (tx, rx) = mpsc::unbounded_channel();
+            let hub = StreamHub::new(
+                self.endpoint.clone(),
+                self.health.clone(),
+                self.reconnect.clone(),
+            );
+            tokio::spawn(hub.run(rx));
+            tx
+        });
+        if commands.send(command).is_err() {
+            error!("Binance stream hub is gone");
+        }
+    }
+}
+
+#[async_trait]
+impl MarketSource for BinanceClient {
+    fn name(&self) -> &str {
+        SOURCE
+    }
+
+    async fn run(&self, asset: AssetDescriptor, tx: Sender<PriceTick>) -> anyhow::Result<()> {
+        match (&self.books, asset.source.parse_option("depth")) {
+            (Some(store), Some(true)) => {
+                let levels = asset
+                    .source
+                    .parse_option("depth_levels")
+                    .unwrap_or(DEFAULT_DEPTH_LEVELS);
+                self.spawn_order_book(&asset.symbol, levels, store.clone());
+            }
+            (None, Some(true)) => {
+                warn!(symbol = %asset.symbol, "Order book requested but not enabled");
+            }
+            _ => {}
+        }
+
+        self.stream_trades(&asset.symbol, tx).await;
+        self.stop_order_book(&asset.symbol);
+        Ok(())
+    }
+
+    /// The requested primary bars from klines no longer than a bar, preceded by
+    /// coarser klines reaching back far enough to fill the longest timeframe.
+    async fn history(
+        &self,
+        asset: &AssetDescriptor,
+        request: &HistoryRequest,
+    ) -> anyhow::Result<Vec<PriceTick>> {
+        let symbol = &asset.symbol;
+        let (fine, fine_secs) = fine_interval(request.bar_duration);
+        let per_bar = usize::try_from(request.bar_duration.num_seconds() / fine_secs)
+            .unwrap_or(1)
+            .max(1);
+        let mut klines = self
+            .fetch_klines(symbol, fine, request.bars * per_bar, None)
+            .await?;
+
+        let covered = klines
+            .first()
+            .map(|first| (first.open_time, Utc::now() - first.open_time));
+        if let Some((start, covered)) = covered
+            && covered < request.span
+        {
+            let remaining = request.span - covered;
+            let (coarse, coarse_secs) = coarse_interval(remaining, fine_secs);
+            let rows = usize::try_from(
+                remaining
+                    .num_seconds()
+                    .unsigned_abs()
+                    .div_ceil(coarse_secs.unsigned_abs()),
+            )
+            .unwrap_or(KLINE_LIMIT);
+            match self.fetch_klines(symbol, coarse, rows, Some(start)).await {
+                Ok(mut earlier) => {
+                    earlier.append(&mut klines);
+                    klines = earlier;
+                }
+                Err(err) => {
+                    warn!(%symbol, %err, "Longer Binance history failed - seeding primary bars only");
                 }
-                warn!(%symbol, "Reconnecting Binance stream in 3s");
-                sleep(Duration::from_secs(3)).await;
             }
+        }
+        Ok(klines
+            .iter()
+            .flat_map(|kline| kline.to_ticks(symbol))
+            .collect())
+    }
+}
+
+/// The longest kline interval no longer than `bar`, so every primary bar is
+/// built from whole klines.
+fn fine_interval(bar: chrono::Duration) -> (&'static str, i64) {
+    KLINE_INTERVALS
+        .iter()
+        .rev()
+        .find(|(_, secs)| *secs <= bar.num_seconds())
+        .copied()
+        .unwrap_or(KLINE_INTERVALS[0])
+}
+
+/// The shortest kline interval of at least `min_secs` that covers `span` in
+/// one request.
+fn coarse_interval(span: chrono::Duration, min_secs: i64) -> (&'static str, i64) {
+    let limit = KLINE_LIMIT as i64;
+    KLINE_INTERVALS
+        .iter()
+        .find(|(_, secs)| *secs >= min_secs && *secs * limit >= span.num_seconds())
+        .copied()
+        .unwrap_or(KLINE_INTERVALS[KLINE_INTERVALS.len() - 1])
+}
+
+/// One OHLCV kline.
+#[derive(Debug, Clone, PartialEq)]
+struct Kline {
+    open_time: DateTime<Utc>,
+    close_time: DateTime<Utc>,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+}
+
+impl Kline {
+    /// Kline rows are `[open_time, open, high, low, close, volume, close_time, ...]`
+    /// with prices and volumes as strings.
+    fn from_row(row: &[serde_json::Value]) -> Option<Self> {
+        let number = |idx: usize| row.get(idx)?.as_str()?.parse::<f64>().ok();
+        let time = |idx: usize| Utc.timestamp_millis_opt(row.get(idx)?.as_i64()?).single();
+        Some(Self {
+            open_time: time(0)?,
+            close_time: time(6)?,
+            open: number(1)?,
+            high: number(2)?,
+            low: number(3)?,
+            close: number(4)?,
+            volume: number(5)?,
         })
     }
 
-    async fn run_stream(
-        endpoint: &str,
-        symbol: &str,
-        tx: &Sender<PriceTick>,
-    ) -> anyhow::Result<()> {
-        let url = format!(
-            "{}/ws/{}@trade",
-            endpoint.trim_end_matches('/'),
-            symbol.to_lowercase()
-        );
-        info!(%symbol, %url, "Connecting to Binance");
+    /// Open, both extremes and close as ticks spread across the kline, so bars
+    /// built from them keep its full range. The extremes come in the order
+    /// the price most likely took them, and each tick carries a quarter of
+    /// the volume.
+    fn to_ticks(&self, symbol: &str) -> [PriceTick; 4] {
+        let (first, second) = if self.close >= self.open {
+            (self.low, self.high)
+        } else {
+            (self.high, self.low)
+        };
+        let third = (self.close_time - self.open_time) / 3;
+        let tick = |price: f64, timestamp: DateTime<Utc>| PriceTick {
+            symbol: symbol.to_string(),
+            price,
+            volume: self.volume / 4.0,
+            timestamp,
+            side: None,
+        };
+        [
+            tick(self.open, self.open_time),
+            tick(first, self.open_time + third),
+            tick(second, self.open_time + third * 2),
+            tick(self.close, self.close_time),
+        ]
+    }
+}
 
-        let (ws_stream, _) = timeout(CONNECTION_TIMEOUT, connect_async(&url)).await??;
-        let (_, mut stream) = ws_stream.split();
+#[derive(Debug, Clone, Copy)]
+enum StreamKind {
+    Trade,
+    Depth,
+}
+
+fn stream_name(symbol: &str, kind: StreamKind) -> String {
+    let symbol = symbol.to_lowercase();
+    match kind {
+        StreamKind::Trade => format!("{symbol}@trade"),
+        StreamKind::Depth => format!("{symbol}@depth@100ms"),
+    }
+}
+
+#[derive(Debug)]
+enum StreamSink {
+    Trades(Sender<PriceTick>),
+    Depth(Sender<DepthUpdate>),
+}
+
+/// One consumer of a stream. The hub never waits on a subscriber, since one
+/// slow pipeline would otherwise stall every stream on the connection; events
+/// it cannot take are dropped and counted instead.
+#[derive(Debug)]
+struct Subscriber {
+    symbol: String,
+    sink: StreamSink,
+    dropped: u64,
+    warned_at: Option<Instant>,
+}
+
+impl Subscriber {
+    fn new(symbol: String, sink: StreamSink) -> Self {
+        Self {
+            symbol,
+            sink,
+            dropped: 0,
+            warned_at: None,
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        match &self.sink {
+            StreamSink::Trades(tx) => tx.is_closed(),
+            StreamSink::Depth(tx) => tx.is_closed(),
+        }
+    }
+
+    /// Hands an event to this subscriber; returns `false` once it has gone away.
+    ///
+    /// Dropped trades are reported to `health`. Dropped depth diffs need no
+    /// report: the order book sees the sequence gap and resyncs.
+    fn deliver(&mut self, event: &StreamEvent, health: &FeedHealthStore) -> bool {
+        // `Err(true)` when the channel is full, `Err(false)` once it is closed.
+        let sent = match (&self.sink, event) {
+            (StreamSink::Trades(tx), StreamEvent::Trade(trade)) => tx
+                .try_send(trade.to_tick(&self.symbol))
+                .map_err(|err| matches!(err, TrySendError::Full(_))),
+            (StreamSink::Depth(tx), StreamEvent::Depth(update)) => tx
+                .try_send(update.clone())
+                .map_err(|err| matches!(err, TrySendError::Full(_))),
+            _ => Ok(()),
+        };
+        match sent {
+            Ok(()) => true,
+            Err(true) => {
+                if matches!(self.sink, StreamSink::Trades(_)) {
+                    health.record_dropped(SOURCE, &self.symbol);
+                }
+                self.record_drop();
+                true
+            }
+            Err(false) => false,
+        }
+    }
+
+    fn record_drop(&mut self) {
+        self.dropped += 1;
+        if self
+            .warned_at
+            .is_none_or(|at| at.elapsed() >= DROP_WARN_INTERVAL)
+        {
+            self.warned_at = Some(Instant::now());
+            warn!(
+                symbol = %self.symbol,
+                dropped = self.dropped,
+                "Subscriber backlogged - dropping events"
+            );
+        }
+    }
+}
+
+/// Owns the combined-stream connection and the per-stream subscriber table.
+struct StreamHub {
+    endpoint: String,
+    health: FeedHealthStore,
+    backoff: Backoff,
+    subscribers: HashMap<String, Vec<Subscriber>>,
+    next_request_id: u64,
+}
+
+impl StreamHub {
+    fn new(endpoint: String, health: FeedHealthStore, reconnect: ReconnectPolicy) -> Self {
+        Self {
+            endpoint,
+            health,
+            backoff: reconnect.backoff(),
+            subscribers: HashMap::new(),
+            next_request_id: 1,
+        }
+    }
 
-        while let Some(message) = timeout(HEARTBEAT_TIMEOUT, stream.next()).await? {
-            let message = message?;
-            if !message.is_text() {
+    /// Every symbol with a live subscription; trade and depth streams share one entry.
+    fn symbols(&self) -> BTreeSet<String> {
+        self.subscribers
+            .values()
+            .flatten()
+            .map(|sub| sub.symbol.clone())
+            .collect()
+    }
+
+    async fn run(mut self, mut commands: UnboundedReceiver<HubCommand>) {
+        loop {
+            if self.subscribers.is_empty() {
+                match commands.recv().await {
+                    Some(command) => {
+                        self.apply(command);
+                        continue;
+                    }
+                    None => return,
+                }
+            }
+
+            let result = self.run_connection(&mut commands).await;
+            let symbols = self.symbols();
+            match result {
+                Ok(true) => return,
+                Ok(false) => {}
+                Err(err) => {
+                    error!(%err, "Binance combined stream failed");
+                    for symbol in &symbols {
+                        self.health.record_error(SOURCE, symbol, &err);
+                    }
+                }
+            }
+            if self.subscribers.is_empty() {
                 continue;
             }
-            let trade: BinanceTrade = serde_json::from_str(message.to_text()?)?;
+            let health = &self.health;
+            self.backoff
+                .wait("Binance combined stream", |backoff| {
+                    for symbol in &symbols {
+                        health.record_backoff(SOURCE, symbol, backoff);
+                    }
+                })
+                .await;
+            for symbol in &symbols {
+                self.health.record_reconnect(SOURCE, symbol);
+            }
+        }
+    }
+
+    /// Applies a command to the subscriber table and returns the stream that
+    /// needs a SUBSCRIBE (`true`) or UNSUBSCRIBE (`false`) request, if any.
+    fn apply(&mut self, command: HubCommand) -> Option<(bool, String)> {
+        match command {
+            HubCommand::Subscribe { stream, subscriber } => {
+                let entry = self.subscribers.entry(stream.clone()).or_default();
+                entry.retain(|sub| !sub.is_closed());
+                let is_new = entry.is_empty();
+                entry.push(subscriber);
+                is_new.then_some((true, stream))
+            }
+            HubCommand::Unsubscribe { stream } => {
+                self.subscribers.remove(&stream).map(|_| (false, stream))
+            }
+        }
+    }
+
+    fn combined_url(&self) -> String {
+        let mut streams: Vec<&str> = self.subscribers.keys().map(String::as_str).collect();
+        streams.sort_unstable();
+        format!(
+            "{}/stream?streams={}",
+            self.endpoint.trim_end_matches('/'),
+            streams.join("/")
+        )
+    }
+
+    /// Applies every command already queued, collecting the subscription
+    /// changes they need into `pending`.
+    fn apply_ready(
+        &mut self,
+        command: HubCommand,
+        commands: &mut UnboundedReceiver<HubCommand>,
+        pending: &mut PendingRequests,
+    ) {
+        let mut next = Some(command);
+        while let Some(command) = next {
+            if let Some((subscribe, stream)) = self.apply(command) {
+                pending.push(subscribe, stream);
+            }
+            next = commands.try_recv().ok();
+        }
+    }
+
+    /// Runs one connection until it fails or drops. Returns `Ok(true)` when the
+    /// command channel is closed and the hub should shut down.
+    ///
+    /// Subscription changes are batched into one request per method and sent
+    /// at most every `REQUEST_INTERVAL`, so a burst of assets subscribing at
+    /// startup stays under Binance's message rate limit.
+    async fn run_connection(
+        &mut self,
+        commands: &mut UnboundedReceiver<HubCommand>,
+    ) -> anyhow::Result<bool> {
+        let url = self.combined_url();
+        info!(streams = self.subscribers.len(), %url, "Connecting to Binance");
 
-            let price = trade.p.parse::<f64>().unwrap_or_default();
-            let volume = trade.q.parse::<f64>().unwrap_or(0.0);
-            let ts = Utc
-                .timestamp_millis_opt(trade.event_time)
-                .single()
-                .unwrap_or_else(|| Utc::now());
+        let (ws_stream, _) = timeout(CONNECTION_TIMEOUT, connect_async(&url)).await??;
+        let (mut sink, mut stream) = ws_stream.split();
+        for symbol in self.symbols() {
+            self.health.set_state(SOURCE, &symbol, FeedState::Connected);
+        }
+        self.backoff.connected();
+
+        // Only inbound frames move the deadline; command traffic must not
+        // keep a silent connection alive.
+        let mut last_message = time::Instant::now();
+        let mut pending = PendingRequests::default();
+        let mut next_request = time::Instant::now();
+        loop {
+            tokio::select! {
+                command = commands.recv() => {
+                    let Some(command) = command else { return Ok(true) };
+                    self.apply_ready(command, commands, &mut pending);
+                    if self.subscribers.is_empty() {
+                        return Ok(false);
+                    }
+                }
+                () = sleep_until(next_request), if !pending.is_empty() => {
+                    if let Some((method, streams)) = pending.next_request() {
+                        sink.send(self.request(method, &streams)).await?;
+                    }
+                    next_request = time::Instant::now() + REQUEST_INTERVAL;
+                }
+                () = sleep_until(last_message + HEARTBEAT_TIMEOUT) => {
+                    anyhow::bail!(
+                        "No message from Binance in {}s",
+                        HEARTBEAT_TIMEOUT.as_secs()
+                    );
+                }
+                message = stream.next() => {
+                    let Some(message) = message else { return Ok(false) };
+                    let message = message?;
+                    last_message = time::Instant::now();
+                    if !message.is_text() {
+                        continue;
+                    }
+                    if let Some(stream) = self.dispatch(message.to_text()?) {
+                        pending.push(false, stream);
+                        if self.subscribers.is_empty() {
+                            return Ok(false);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn request(&mut self, method: &str, streams: &[String]) -> Message {
+        info!(streams = %streams.join(","), method, "Updating Binance subscription");
+        let request = json!({
+            "method": method,
+            "params": streams,
+            "id": self.next_request_id,
+        });
+        self.next_request_id += 1;
+        Message::Text(request.to_string())
+    }
+
+    /// Routes one combined-stream message to its subscribers. Returns the stream
+    /// name to unsubscribe if every subscriber for it has gone away.
+    ///
+    /// A frame that fails to parse is logged and skipped, and counted as an
+    /// error against the symbols it was meant for; it never takes down the
+    /// connection every other stream shares.
+    fn dispatch(&mut self, raw: &str) -> Option<String> {
+        let message = match serde_json::from_str(raw) {
+            Ok(message) => message,
+            Err(err) => {
+                warn!(%err, %raw, "Unreadable Binance message - skipping");
+                return None;
+            }
+        };
+        let CombinedMessage::Data { stream, data } = message else {
+            debug!(%raw, "Binance control message");
+            return None;
+        };
+        self.backoff.delivered();
+        let subscribers = self.subscribers.get_mut(&stream)?;
+
+        let event = if stream.contains("@depth") {
+            serde_json::from_value(data).map(StreamEvent::Depth)
+        } else {
+            serde_json::from_value(data).map(StreamEvent::Trade)
+        };
+        let event = match event {
+            Ok(event) => event,
+            Err(err) => {
+                warn!(%stream, %err, "Malformed Binance payload - skipping");
+                let symbols: BTreeSet<&str> =
+                    subscribers.iter().map(|sub| sub.symbol.as_str()).collect();
+                for symbol in symbols {
+                    self.health.record_error(SOURCE, symbol, &err);
+                }
+                return None;
+            }
+        };
+        subscribers.retain_mut(|sub| sub.deliver(&event, &self.health));
 
-            let tick = PriceTick {
-                symbol: symbol.to_string(),
-                price,
-                volume,
-                timestamp: ts,
+        if subscribers.is_empty() {
+            self.subscribers.remove(&stream);
+            return Some(stream);
+        }
+        None
+    }
+}
+
+/// Subscription changes waiting to be sent. A change that undoes one still
+/// pending cancels it instead of queueing a second request.
+#[derive(Debug, Default)]
+struct PendingRequests {
+    subscribe: BTreeSet<String>,
+    unsubscribe: BTreeSet<String>,
+}
+
+impl PendingRequests {
+    fn push(&mut self, subscribe: bool, stream: String) {
+        if subscribe {
+            if !self.unsubscribe.remove(&stream) {
+                self.subscribe.insert(stream);
+            }
+        } else if !self.subscribe.remove(&stream) {
+            self.unsubscribe.insert(stream);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.subscribe.is_empty() && self.unsubscribe.is_empty()
+    }
+
+    /// The next request to send, unsubscribes first.
+    fn next_request(&mut self) -> Option<(&'static str, Vec<String>)> {
+        if !self.unsubscribe.is_empty() {
+            let streams = std::mem::take(&mut self.unsubscribe);
+            return Some(("UNSUBSCRIBE", streams.into_iter().collect()));
+        }
+        if !self.subscribe.is_empty() {
+            let streams = std::mem::take(&mut self.subscribe);
+            return Some(("SUBSCRIBE", streams.into_iter().collect()));
+        }
+        None
+    }
+}
+
+/// Applies Binance's diff-depth sync procedure: buffer diffs, load a REST
+/// snapshot, drop diffs it already covers, then require each diff to continue
+/// exactly where the previous one ended. Any gap triggers a fresh snapshot.
+struct DepthSync {
+    rest_base: String,
+    http: Client,
+    reconnect: ReconnectPolicy,
+    symbol: String,
+    levels: usize,
+    store: OrderBookStore,
+}
+
+impl DepthSync {
+    async fn run(self, mut rx: Receiver<DepthUpdate>) {
+        let mut backoff = self.reconnect.backoff();
+
+        'resync: loop {
+            let snapshot = match self.fetch_snapshot().await {
+                Ok(snapshot) => snapshot,
+                Err(err) => {
+                    warn!(symbol = %self.symbol, %err, "Depth snapshot failed");
+                    backoff
+                        .wait(&format!("Binance depth {}", self.symbol), |_| {})
+                        .await;
+                    continue;
+                }
             };
+            backoff.connected();
 
-            if tx.send(tick).await.is_err() {
-                break;
+            let mut book = SyncedBook::new(&snapshot);
+            while let Some(update) = rx.recv().await {
+                match book.apply(&update) {
+                    DiffOutcome::Stale => {}
+                    DiffOutcome::Gap => {
+                        warn!(symbol = %self.symbol, "Depth stream gap - resyncing order book");
+                        continue 'resync;
+                    }
+                    DiffOutcome::Applied => {
+                        backoff.delivered();
+                        if let Some(metrics) = book.book.metrics(self.levels) {
+                            self.store.update(&self.symbol, metrics);
+                        }
+                    }
+                }
             }
+            return;
         }
+    }
 
-        Ok(())
+    async fn fetch_snapshot(&self) -> anyhow::Result<DepthSnapshot> {
+        let url = format!(
+            "{}/api/v3/depth?symbol={}&limit={}",
+            self.rest_base.trim_end_matches('/'),
+            self.symbol.to_uppercase(),
+            SNAPSHOT_LIMIT
+        );
+        info!(symbol = %self.symbol, "Fetching Binance depth snapshot");
+        Ok(self
+            .http
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffOutcome {
+    /// Already covered by the snapshot or an earlier diff.
+    Stale,
+    Applied,
+    /// Updates were missed; the book needs a fresh snapshot.
+    Gap,
+}
+
+/// An order book seeded from a REST snapshot and advanced by depth diffs,
+/// tracking update ids so missed diffs are detected.
+#[derive(Debug)]
+struct SyncedBook {
+    book: OrderBook,
+    last_id: u64,
+    /// Whether a diff has been applied on top of the snapshot yet.
+    synced: bool,
+}
+
+impl SyncedBook {
+    fn new(snapshot: &DepthSnapshot) -> Self {
+        let mut book = OrderBook::new();
+        apply_levels(&mut book, Side::Bid, &snapshot.bids);
+        apply_levels(&mut book, Side::Ask, &snapshot.asks);
+        Self {
+            book,
+            last_id: snapshot.last_update_id,
+            synced: false,
+        }
+    }
+
+    /// The first diff only has to straddle the snapshot; every later one must
+    /// start right after the previous one ended.
+    fn apply(&mut self, update: &DepthUpdate) -> DiffOutcome {
+        if update.final_id <= self.last_id {
+            return DiffOutcome::Stale;
+        }
+        let continues = if self.synced {
+            update.first_id == self.last_id + 1
+        } else {
+            update.first_id <= self.last_id + 1
+        };
+        if !continues {
+            return DiffOutcome::Gap;
+        }
+
+        self.synced = true;
+        apply_levels(&mut self.book, Side::Bid, &update.bids);
+        apply_levels(&mut self.book, Side::Ask, &update.asks);
+        self.last_id = update.final_id;
+        DiffOutcome::Applied
+    }
+}
+
+fn apply_levels(book: &mut OrderBook, side: Side, levels: &[(String, String)]) {
+    for (price, quantity) in levels {
+        if let (Ok(price), Ok(quantity)) = (price.parse(), quantity.parse()) {
+            book.set_level(side, price, quantity);
+        }
+    }
+}
+
+#[derive(Debug)]
+enum StreamEvent {
+    Trade(BinanceTrade),
+    Depth(DepthUpdate),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum CombinedMessage {
+    Data {
+        stream: String,
+        data: serde_json::Value,
+    },
+    /// Subscription acks and other control frames.
+    Other {},
+}
+
 #[derive(Debug, Deserialize)]
 struct BinanceTrade {
     #[serde(rename = "p")]
@@ -99,4 +947,430 @@ struct BinanceTrade {
     q: String,
     #[serde(rename = "T")]
     event_time: i64,
+    /// Buyer is the maker, so the aggressor sold.
+    #[serde(rename = "m", default)]
+    buyer_is_maker: bool,
+}
+
+impl BinanceTrade {
+    fn to_tick(&self, symbol: &str) -> PriceTick {
+        let ts = Utc
+            .timestamp_millis_opt(self.event_time)
+            .single()
+            .unwrap_or_else(Utc::now);
+        PriceTick {
+            symbol: symbol.to_string(),
+            price: self.p.parse::<f64>().unwrap_or_default(),
+            volume: self.q.parse::<f64>().unwrap_or(0.0),
+            timestamp: ts,
+            side: Some(if self.buyer_is_maker {
+                TradeSide::Sell
+            } else {
+                TradeSide::Buy
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct DepthUpdate {
+    #[serde(rename = "U")]
+    first_id: u64,
+    #[serde(rename = "u")]
+    final_id: u64,
+    #[serde(rename = "b")]
+    bids: Vec<(String, String)>,
+    #[serde(rename = "a")]
+    asks: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct DepthSnapshot {
+    #[serde(rename = "lastUpdateId")]
+    last_update_id: u64,
+    bids: Vec<(String, String)>,
+    asks: Vec<(String, String)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::bars::BarBuilder;
+
+    const TRADE: &str = include_str!("../../tests/fixtures/binance/trade.json");
+    const DEPTH: &str = include_str!("../../tests/fixtures/binance/depth.json");
+    const SUBSCRIBE_ACK: &str = include_str!("../../tests/fixtures/binance/subscribe_ack.json");
+
+    fn hub() -> StreamHub {
+        StreamHub::new(
+            "wss://stream.binance.com:9443/".to_string(),
+            FeedHealthStore::new(),
+            ReconnectPolicy::default(),
+        )
+    }
+
+    fn subscribe_trades(
+        hub: &mut StreamHub,
+        capacity: usize,
+    ) -> (Option<(bool, String)>, Receiver<PriceTick>) {
+        let (tx, rx) = mpsc::channel(capacity);
+        let applied = hub.apply(HubCommand::Subscribe {
+            stream: stream_name("BTCUSDT", StreamKind::Trade),
+            subscriber: Subscriber::new("BTCUSDT".to_string(), StreamSink::Trades(tx)),
+        });
+        (applied, rx)
+    }
+
+    #[test]
+    fn only_the_first_subscriber_subscribes_the_stream() {
+        let mut hub = hub();
+        let (first, _rx1) = subscribe_trades(&mut hub, 8);
+        assert_eq!(first, Some((true, "btcusdt@trade".to_string())));
+        let (second, _rx2) = subscribe_trades(&mut hub, 8);
+        assert_eq!(second, None);
+        assert_eq!(hub.subscribers["btcusdt@trade"].len(), 2);
+
+        let removed = hub.apply(HubCommand::Unsubscribe {
+            stream: "btcusdt@trade".to_string(),
+        });
+        assert_eq!(removed, Some((false, "btcusdt@trade".to_string())));
+        assert!(hub.subscribers.is_empty());
+        let again = hub.apply(HubCommand::Unsubscribe {
+            stream: "btcusdt@trade".to_string(),
+        });
+        assert_eq!(again, None);
+    }
+
+    #[test]
+    fn resubscribing_after_every_receiver_closed_is_new() {
+        let mut hub = hub();
+        let (_, rx) = subscribe_trades(&mut hub, 8);
+        drop(rx);
+        // The closed subscriber is pruned, so the stream counts as new again.
+        let (applied, _rx) = subscribe_trades(&mut hub, 8);
+        assert_eq!(applied, Some((true, "btcusdt@trade".to_string())));
+        assert_eq!(hub.subscribers["btcusdt@trade"].len(), 1);
+    }
+
+    #[test]
+    fn combined_url_lists_every_stream() {
+        let mut hub = hub();
+        let (_, _rx) = subscribe_trades(&mut hub, 8);
+        let (tx, _depth_rx) = mpsc::channel(8);
+        hub.apply(HubCommand::Subscribe {
+            stream: stream_name("ETHUSDT", StreamKind::Depth),
+            subscriber: Subscriber::new("ETHUSDT".to_string(), StreamSink::Depth(tx)),
+        });
+        assert_eq!(
+            hub.combined_url(),
+            "wss://stream.binance.com:9443/stream?streams=btcusdt@trade/ethusdt@depth@100ms"
+        );
+    }
+
+    #[test]
+    fn dispatch_routes_trades_to_subscribers() {
+        let mut hub = hub();
+        let (_, mut rx) = subscribe_trades(&mut hub, 8);
+        assert_eq!(hub.dispatch(TRADE), None);
+
+        let tick = rx.try_recv().expect("tick");
+        assert_eq!(tick.symbol, "BTCUSDT");
+        assert_eq!(tick.price, 64_012.55);
+        assert_eq!(tick.volume, 0.0125);
+        assert_eq!(tick.timestamp.timestamp_millis(), 1_714_564_801_123);
+        // The buyer was the maker, so the aggressor sold.
+        assert_eq!(tick.side, Some(TradeSide::Sell));
+    }
+
+    #[test]
+    fn dispatch_routes_depth_diffs_by_stream() {
+        let mut hub = hub();
+        let (_, mut trades) = subscribe_trades(&mut hub, 8);
+        let (tx, mut depth) = mpsc::channel(8);
+        hub.apply(HubCommand::Subscribe {
+            stream: stream_name("BTCUSDT", StreamKind::Depth),
+            subscriber: Subscriber::new("BTCUSDT".to_string(), StreamSink::Depth(tx)),
+        });
+
+        assert_eq!(hub.dispatch(DEPTH), None);
+        let update = depth.try_recv().expect("depth update");
+        assert_eq!((update.first_id, update.final_id), (157, 160));
+        assert_eq!(update.bids.len(), 2);
+        assert_eq!(
+            update.asks,
+            [("64013.00000000".to_string(), "0.75000000".to_string())]
+        );
+        assert!(trades.try_recv().is_err());
+    }
+
+    #[test]
+    fn dispatch_ignores_control_frames_and_unknown_streams() {
+        let mut hub = hub();
+        assert_eq!(hub.dispatch(SUBSCRIBE_ACK), None);
+        // Nobody is subscribed to the trade stream.
+        assert_eq!(hub.dispatch(TRADE), None);
+        assert_eq!(hub.dispatch("not json"), None);
+    }
+
+    #[test]
+    fn dispatch_skips_malformed_payloads_and_counts_them() {
+        let mut hub = hub();
+        let (_, mut rx) = subscribe_trades(&mut hub, 8);
+        hub.health
+            .register(SOURCE, "BTCUSDT", Duration::from_secs(30));
+        let malformed = r#"{"stream":"btcusdt@trade","data":{"e":"trade","p":"oops"}}"#;
+
+        assert_eq!(hub.dispatch(malformed), None);
+        let status = hub.health.status(SOURCE, "BTCUSDT").expect("registered");
+        assert_eq!(status.errors, 1);
+
+        // The stream keeps flowing afterwards.
+        assert_eq!(hub.dispatch(TRADE), None);
+        assert!(rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn pending_requests_batch_streams_and_cancel_reversals() {
+        let mut pending = PendingRequests::default();
+        pending.push(true, "btcusdt@trade".to_string());
+        pending.push(true, "ethusdt@trade".to_string());
+        pending.push(false, "solusdt@trade".to_string());
+        // Subscribing again before the unsubscribe went out cancels it.
+        pending.push(true, "solusdt@trade".to_string());
+        pending.push(false, "adausdt@trade".to_string());
+        // Unsubscribing before the subscribe went out cancels it.
+        pending.push(true, "xrpusdt@trade".to_string());
+        pending.push(false, "xrpusdt@trade".to_string());
+
+        assert_eq!(
+            pending.next_request(),
+            Some(("UNSUBSCRIBE", vec!["adausdt@trade".to_string()]))
+        );
+        assert_eq!(
+            pending.next_request(),
+            Some((
+                "SUBSCRIBE",
+                vec!["btcusdt@trade".to_string(), "ethusdt@trade".to_string()]
+            ))
+        );
+        assert!(pending.is_empty());
+        assert_eq!(pending.next_request(), None);
+    }
+
+    #[test]
+    fn request_carries_every_stream() {
+        let mut hub = hub();
+        let streams = ["btcusdt@trade".to_string(), "ethusdt@trade".to_string()];
+        let Message::Text(text) = hub.request("SUBSCRIBE", &streams) else {
+            panic!("text frame");
+        };
+        let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(request["params"], json!(streams));
+        assert_eq!(request["method"], "SUBSCRIBE");
+    }
+
+    #[tokio::test]
+    async fn unsubscribe_stops_the_order_book_task() {
+        let client = BinanceClient::new("ws://127.0.0.1:9/").with_rest_base("http://127.0.0.1:9");
+        let store = OrderBookStore::new();
+        store.update("BTCUSDT", crate::models::BookMetrics::default());
+        let task = client.spawn_order_book("BTCUSDT", 10, store.clone());
+
+        client.unsubscribe("BTCUSDT");
+        let err = task.await.expect_err("order book task should be aborted");
+        assert!(err.is_cancelled());
+        assert!(store.latest("BTCUSDT").is_none());
+        assert!(client.order_books.lock().is_empty());
+    }
+
+    #[test]
+    fn dispatch_unsubscribes_once_every_receiver_is_gone() {
+        let mut hub = hub();
+        let (_, rx) = subscribe_trades(&mut hub, 8);
+        drop(rx);
+        assert_eq!(hub.dispatch(TRADE), Some("btcusdt@trade".to_string()));
+        assert!(hub.subscribers.is_empty());
+    }
+
+    fn levels(levels: &[(&str, &str)]) -> Vec<(String, String)> {
+        levels
+            .iter()
+            .map(|(price, qty)| (price.to_string(), qty.to_string()))
+            .collect()
+    }
+
+    fn snapshot() -> DepthSnapshot {
+        DepthSnapshot {
+            last_update_id: 100,
+            bids: levels(&[("99.0", "1.0"), ("98.0", "2.0")]),
+            asks: levels(&[("101.0", "1.0"), ("102.0", "2.0")]),
+        }
+    }
+
+    fn diff(first_id: u64, final_id: u64, bids: &[(&str, &str)]) -> DepthUpdate {
+        DepthUpdate {
+            first_id,
+            final_id,
+            bids: levels(bids),
+            asks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn kline_intervals_follow_the_bars_and_the_span() {
+        let minutes = chrono::Duration::minutes;
+        assert_eq!(fine_interval(chrono::Duration::seconds(5)), ("1s", 1));
+        assert_eq!(fine_interval(minutes(1)), ("1m", 60));
+        assert_eq!(fine_interval(chrono::Duration::seconds(90)), ("1m", 60));
+        assert_eq!(fine_interval(minutes(20)), ("15m", 900));
+
+        // 120 hourly bars need 7200 minutes, more than one request of 1m or 5m klines.
+        let span = chrono::Duration::hours(120);
+        assert_eq!(coarse_interval(span, 60), ("15m", 900));
+        assert_eq!(coarse_interval(chrono::Duration::hours(2), 60), ("1m", 60));
+        assert_eq!(
+            coarse_interval(chrono::Duration::hours(2), 300),
+            ("5m", 300)
+        );
+    }
+
+    #[test]
+    fn history_spans_the_longest_timeframe() {
+        let request = HistoryRequest::new(
+            120,
+            chrono::Duration::minutes(1),
+            chrono::Duration::hours(1),
+        );
+        assert_eq!(request.span, chrono::Duration::hours(120));
+        let request = HistoryRequest::new(
+            120,
+            chrono::Duration::minutes(1),
+            chrono::Duration::minutes(1),
+        );
+        assert_eq!(request.span, chrono::Duration::hours(2));
+    }
+
+    #[test]
+    fn klines_seed_full_ohlcv_bars() {
+        let row = json!([
+            1_714_564_800_000_i64,
+            "64000.00",
+            "64100.00",
+            "63900.00",
+            "63950.00",
+            "12.0",
+            1_714_564_859_999_i64,
+            "768000.0",
+            420
+        ]);
+        let kline = Kline::from_row(row.as_array().unwrap()).expect("kline");
+        let ticks = kline.to_ticks("BTCUSDT");
+        // A falling kline most likely made its high before its low.
+        let prices: Vec<f64> = ticks.iter().map(|tick| tick.price).collect();
+        assert_eq!(prices, [64_000.0, 64_100.0, 63_900.0, 63_950.0]);
+        assert!(
+            ticks
+                .windows(2)
+                .all(|pair| pair[0].timestamp < pair[1].timestamp)
+        );
+
+        let mut bars = BarBuilder::new(chrono::Duration::minutes(1));
+        for tick in &ticks {
+            assert!(bars.push(tick).is_none());
+        }
+        let bar = bars.current().expect("forming bar");
+        assert_eq!(
+            (bar.open, bar.high, bar.low, bar.close),
+            (64_000.0, 64_100.0, 63_900.0, 63_950.0)
+        );
+        assert_eq!(bar.volume, 12.0);
+    }
+
+    fn best_bid(book: &SyncedBook) -> f64 {
+        book.book.metrics(10).unwrap().best_bid
+    }
+
+    #[test]
+    fn depth_sync_skips_diffs_the_snapshot_covers() {
+        let mut book = SyncedBook::new(&snapshot());
+        assert_eq!(
+            book.apply(&diff(90, 95, &[("99.5", "1.0")])),
+            DiffOutcome::Stale
+        );
+        assert_eq!(
+            book.apply(&diff(96, 100, &[("99.5", "1.0")])),
+            DiffOutcome::Stale
+        );
+        assert_eq!(best_bid(&book), 99.0);
+    }
+
+    #[test]
+    fn depth_sync_accepts_a_first_diff_straddling_the_snapshot() {
+        let mut book = SyncedBook::new(&snapshot());
+        assert_eq!(
+            book.apply(&diff(95, 103, &[("99.5", "1.0")])),
+            DiffOutcome::Applied
+        );
+        assert_eq!(best_bid(&book), 99.5);
+        assert_eq!(book.last_id, 103);
+
+        assert_eq!(
+            book.apply(&diff(104, 106, &[("99.5", "0"), ("99.0", "0")])),
+            DiffOutcome::Applied
+        );
+        assert_eq!(best_bid(&book), 98.0);
+        assert_eq!(book.last_id, 106);
+    }
+
+    #[test]
+    fn depth_sync_flags_gaps() {
+        // The first diff starts past the snapshot.
+        let mut book = SyncedBook::new(&snapshot());
+        assert_eq!(book.apply(&diff(102, 105, &[])), DiffOutcome::Gap);
+
+        // Once synced, diffs must follow on exactly.
+        let mut book = SyncedBook::new(&snapshot());
+        assert_eq!(book.apply(&diff(101, 103, &[])), DiffOutcome::Applied);
+        assert_eq!(
+            book.apply(&diff(105, 107, &[("99.5", "1.0")])),
+            DiffOutcome::Gap
+        );
+        assert_eq!(best_bid(&book), 99.0);
+        // An overlapping diff is no longer acceptable either.
+        assert_eq!(book.apply(&diff(103, 107, &[])), DiffOutcome::Gap);
+    }
+
+    #[test]
+    fn depth_sync_resyncs_from_a_fresh_snapshot() {
+        let mut book = SyncedBook::new(&snapshot());
+        assert_eq!(book.apply(&diff(101, 103, &[])), DiffOutcome::Applied);
+        assert_eq!(book.apply(&diff(110, 112, &[])), DiffOutcome::Gap);
+
+        let mut fresh = snapshot();
+        fresh.last_update_id = 111;
+        fresh.bids = levels(&[("97.0", "1.0")]);
+        let mut book = SyncedBook::new(&fresh);
+        assert_eq!(best_bid(&book), 97.0);
+        assert_eq!(
+            book.apply(&diff(110, 112, &[("97.5", "1.0")])),
+            DiffOutcome::Applied
+        );
+        assert_eq!(book.apply(&diff(113, 113, &[])), DiffOutcome::Applied);
+        assert_eq!(best_bid(&book), 97.5);
+    }
+
+    #[test]
+    fn backlogged_subscribers_drop_trades_and_report_them() {
+        let mut hub = hub();
+        hub.health
+            .register(SOURCE, "BTCUSDT", Duration::from_secs(60));
+        let (_, mut rx) = subscribe_trades(&mut hub, 1);
+        for _ in 0..3 {
+            assert_eq!(hub.dispatch(TRADE), None);
+        }
+        assert_eq!(hub.subscribers["btcusdt@trade"][0].dropped, 2);
+        assert_eq!(hub.health.status(SOURCE, "BTCUSDT").unwrap().dropped, 2);
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_err());
+    }
 }
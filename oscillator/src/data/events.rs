@@ -0,0 +1,509 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    mem::{Discriminant, discriminant},
+};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+use crate::models::{Bar, CrossDirection, MarketEvent, MarketEventKind, PriceTick, SourceSpec};
+
+/// RSI levels whose crossings are reported.
+const RSI_LEVELS: [f64; 2] = [30.0, 70.0];
+/// Closed bars of volume history behind the spike threshold.
+const VOLUME_LOOKBACK: usize = 60;
+const MIN_VOLUME_BARS: usize = 20;
+/// Ticks of trade-size history behind the large-trade threshold.
+const TRADE_LOOKBACK: usize = 500;
+const MIN_TRADES: usize = 50;
+
+/// Event detector thresholds.
+///
+/// Source options (all optional):
+/// - `round_step`: spacing of round-number levels, default one tenth of the
+///   current price's power of ten (`1000` for a `65000` price)
+/// - `volume_sigma`: standard deviations above the mean for a volume spike, default `3`
+/// - `large_trade`: trade volume that counts as large; by default `25` times
+///   the recent average trade
+/// - `gap_pct`: move across a reconnect reported as a gap, default `0.25`
+/// - `session_warmup_mins`: minutes into a session before new highs and lows
+///   are reported, default `10`
+/// - `event_cooldown_secs`: minimum spacing between events of one type, default `30`
+#[derive(Debug, Clone)]
+pub struct EventConfig {
+    pub round_step: Option<f64>,
+    pub volume_sigma: f64,
+    pub large_trade: Option<f64>,
+    pub large_trade_multiple: f64,
+    pub gap_pct: f64,
+    pub session_warmup: Duration,
+    pub cooldown: Duration,
+}
+
+impl Default for EventConfig {
+    fn default() -> Self {
+        Self {
+            round_step: None,
+            volume_sigma: 3.0,
+            large_trade: None,
+            large_trade_multiple: 25.0,
+            gap_pct: 0.25,
+            session_warmup: Duration::minutes(10),
+            cooldown: Duration::seconds(30),
+        }
+    }
+}
+
+impl EventConfig {
+    pub fn from_spec(spec: &SourceSpec) -> Self {
+        let defaults = Self::default();
+        Self {
+            round_step: spec.parse_option("round_step").filter(|step| *step > 0.0),
+            volume_sigma: spec
+                .parse_option("volume_sigma")
+                .unwrap_or(defaults.volume_sigma),
+            large_trade: spec.parse_option("large_trade"),
+            gap_pct: spec.parse_option("gap_pct").unwrap_or(defaults.gap_pct),
+            session_warmup: spec
+                .parse_option("session_warmup_mins")
+                .map(Duration::minutes)
+                .unwrap_or(defaults.session_warmup),
+            cooldown: spec
+                .parse_option("event_cooldown_secs")
+                .map(Duration::seconds)
+                .unwrap_or(defaults.cooldown),
+            ..defaults
+        }
+    }
+}
+
+/// Session extremes, reset at local midnight like the session VWAP.
+#[derive(Debug, Clone)]
+struct Session {
+    day: NaiveDate,
+    started: DateTime<Utc>,
+    high: f64,
+    low: f64,
+}
+
+/// Turns the tick and bar stream into discrete `MarketEvent`s: new session
+/// highs and lows, round-number crossings, volume spikes, RSI 30/70 crossings,
+/// large trades and gaps across reconnects.
+///
+/// Events of one type are spaced by the configured cooldown so a trend making
+/// a new high every tick does not fire on every tick. RSI crossings cool down
+/// per level, so a crossing of 30 never mutes one of 70.
+#[derive(Debug, Clone)]
+pub struct EventDetector {
+    config: EventConfig,
+    timezone: Tz,
+    last: Option<PriceTick>,
+    session: Option<Session>,
+    volumes: VecDeque<f64>,
+    trades: VecDeque<f64>,
+    trade_volume: f64,
+    rsi: Option<f64>,
+    reconnected: bool,
+    last_fired: HashMap<CooldownKey, DateTime<Utc>>,
+    pending: Vec<MarketEvent>,
+}
+
+impl EventDetector {
+    pub fn new(config: EventConfig) -> Self {
+        Self {
+            config,
+            timezone: Tz::UTC,
+            last: None,
+            session: None,
+            volumes: VecDeque::with_capacity(VOLUME_LOOKBACK),
+            trades: VecDeque::with_capacity(TRADE_LOOKBACK),
+            trade_volume: 0.0,
+            rsi: None,
+            reconnected: false,
+            last_fired: HashMap::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Starts sessions at local midnight in `timezone` instead of UTC.
+    pub fn set_timezone(&mut self, timezone: Tz) {
+        self.timezone = timezone;
+    }
+
+    /// The feed reconnected; the next tick is compared against the last one
+    /// before the outage.
+    pub fn reconnected(&mut self) {
+        self.reconnected = true;
+    }
+
+    /// Drains the events detected since the last call.
+    pub fn take(&mut self) -> Vec<MarketEvent> {
+        std::mem::take(&mut self.pending)
+    }
+
+    pub fn on_tick(&mut self, tick: &PriceTick) {
+        let reconnected = std::mem::take(&mut self.reconnected);
+        if let Some(last) = self.last.take() {
+            if reconnected {
+                self.check_gap(&last, tick);
+            }
+            self.check_round_number(last.price, tick);
+        }
+        self.check_session(tick);
+        self.check_trade(tick);
+        self.last = Some(tick.clone());
+    }
+
+    /// Called with each closed bar and the RSI after it.
+    pub fn on_bar(&mut self, bar: &Bar, rsi: f64) {
+        self.check_volume(bar);
+        if let Some(prev) = self.rsi.replace(rsi) {
+            for level in RSI_LEVELS {
+                let direction = if prev < level && rsi >= level {
+                    CrossDirection::Bullish
+                } else if prev >= level && rsi < level {
+                    CrossDirection::Bearish
+                } else {
+                    continue;
+                };
+                self.fire(
+                    bar.end,
+                    bar.close,
+                    MarketEventKind::RsiCross { level, direction },
+                );
+            }
+        }
+    }
+
+    fn check_gap(&mut self, last: &PriceTick, tick: &PriceTick) {
+        if last.price <= 0.0 {
+            return;
+        }
+        let gap_pct = (tick.price - last.price) / last.price * 100.0;
+        if gap_pct.abs() >= self.config.gap_pct {
+            let gap_secs = (tick.timestamp - last.timestamp).num_milliseconds() as f64 / 1000.0;
+            self.fire(
+                tick.timestamp,
+                tick.price,
+                MarketEventKind::ReconnectGap { gap_pct, gap_secs },
+            );
+        }
+    }
+
+    fn check_round_number(&mut self, prev: f64, tick: &PriceTick) {
+        // The default follows the price's magnitude, so it widens as the price
+        // crosses a power of ten.
+        let step = self
+            .config
+            .round_step
+            .unwrap_or_else(|| 10f64.powf(prev.log10().floor() - 1.0));
+        if !step.is_finite() || step <= 0.0 {
+            return;
+        }
+        let (from, to) = ((prev / step).floor(), (tick.price / step).floor());
+        if from == to {
+            return;
+        }
+        // Report the level closest to the new price when a jump crosses several.
+        let (level, direction) = if to > from {
+            (to * step, CrossDirection::Bullish)
+        } else {
+            ((to + 1.0) * step, CrossDirection::Bearish)
+        };
+        self.fire(
+            tick.timestamp,
+            tick.price,
+            MarketEventKind::RoundNumber { level, direction },
+        );
+    }
+
+    fn check_session(&mut self, tick: &PriceTick) {
+        let day = tick.timestamp.with_timezone(&self.timezone).date_naive();
+        let session = match &mut self.session {
+            Some(session) if session.day == day => session,
+            _ => {
+                self.session = Some(Session {
+                    day,
+                    started: tick.timestamp,
+                    high: tick.price,
+                    low: tick.price,
+                });
+                return;
+            }
+        };
+        let settled = tick.timestamp - session.started >= self.config.session_warmup;
+        let kind = if tick.price > session.high {
+            session.high = tick.price;
+            MarketEventKind::SessionHigh
+        } else if tick.price < session.low {
+            session.low = tick.price;
+            MarketEventKind::SessionLow
+        } else {
+            return;
+        };
+        if settled {
+            self.fire(tick.timestamp, tick.price, kind);
+        }
+    }
+
+    fn check_trade(&mut self, tick: &PriceTick) {
+        let threshold = match self.config.large_trade {
+            Some(threshold) => Some(threshold),
+            None if self.trades.len() >= MIN_TRADES => Some(
+                self.trade_volume / self.trades.len() as f64 * self.config.large_trade_multiple,
+            ),
+            None => None,
+        };
+        if let Some(threshold) = threshold
+            && tick.volume > 0.0
+            && tick.volume >= threshold
+        {
+            self.fire(
+                tick.timestamp,
+                tick.price,
+                MarketEventKind::LargeTrade {
+                    volume: tick.volume,
+                    side: tick.side,
+                },
+            );
+        }
+
+        if self.trades.len() == TRADE_LOOKBACK
+            && let Some(old) = self.trades.pop_front()
+        {
+            self.trade_volume -= old;
+        }
+        self.trades.push_back(tick.volume);
+        self.trade_volume += tick.volume;
+    }
+
+    fn check_volume(&mut self, bar: &Bar) {
+        if self.volumes.len() >= MIN_VOLUME_BARS {
+            let count = self.volumes.len() as f64;
+            let mean = self.volumes.iter().sum::<f64>() / count;
+            let std = (self.volumes.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count).sqrt();
+            if std > 0.0 {
+                let sigma = (bar.volume - mean) / std;
+                if sigma >= self.config.volume_sigma {
+                    self.fire(
+                        bar.end,
+                        bar.close,
+                        MarketEventKind::VolumeSpike {
+                            volume: bar.volume,
+                            sigma,
+                        },
+                    );
+                }
+            }
+        }
+        if self.volumes.len() == VOLUME_LOOKBACK {
+            self.volumes.pop_front();
+        }
+        self.volumes.push_back(bar.volume);
+    }
+
+    fn fire(&mut self, at: DateTime<Utc>, price: f64, kind: MarketEventKind) {
+        let key = cooldown_key(&kind);
+        if self
+            .last_fired
+            .get(&key)
+            .is_some_and(|last| at - *last < self.config.cooldown)
+        {
+            return;
+        }
+        self.last_fired.insert(key, at);
+        self.pending.push(MarketEvent { at, price, kind });
+    }
+}
+
+/// Event type, plus the level for RSI crossings.
+type CooldownKey = (Discriminant<MarketEventKind>, Option<u64>);
+
+fn cooldown_key(kind: &MarketEventKind) -> CooldownKey {
+    let level = match kind {
+        MarketEventKind::RsiCross { level, .. } => Some(level.to_bits()),
+        _ => None,
+    };
+    (discriminant(kind), level)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::models::TradeSide;
+
+    fn tick(secs: i64, price: f64, volume: f64) -> PriceTick {
+        PriceTick {
+            symbol: "btcusdt".to_string(),
+            price,
+            volume,
+            timestamp: Utc.timestamp_opt(1_700_006_400 + secs, 0).unwrap(),
+            side: Some(TradeSide::Buy),
+        }
+    }
+
+    fn bar(idx: i64, volume: f64) -> Bar {
+        let start = Utc.timestamp_opt(1_700_006_400, 0).unwrap() + Duration::minutes(idx);
+        Bar {
+            symbol: "btcusdt".to_string(),
+            start,
+            end: start + Duration::minutes(1),
+            open: 100.0,
+            high: 100.0,
+            low: 100.0,
+            close: 100.0,
+            volume,
+            buy_volume: 0.0,
+            sell_volume: 0.0,
+            trades: 1,
+        }
+    }
+
+    fn kinds(detector: &mut EventDetector) -> Vec<MarketEventKind> {
+        detector
+            .take()
+            .into_iter()
+            .map(|event| event.kind)
+            .collect()
+    }
+
+    #[test]
+    fn session_extremes_wait_for_warmup_and_cooldown() {
+        let mut detector = EventDetector::new(EventConfig::default());
+        detector.on_tick(&tick(0, 100.5, 1.0));
+        detector.on_tick(&tick(60, 101.5, 1.0));
+        assert!(kinds(&mut detector).is_empty(), "still warming up");
+
+        detector.on_tick(&tick(700, 102.5, 1.0));
+        assert_eq!(kinds(&mut detector), [MarketEventKind::SessionHigh]);
+        detector.on_tick(&tick(710, 102.7, 1.0));
+        assert!(kinds(&mut detector).is_empty(), "cooling down");
+        detector.on_tick(&tick(720, 99.5, 1.0));
+        assert_eq!(
+            kinds(&mut detector),
+            [
+                MarketEventKind::RoundNumber {
+                    level: 100.0,
+                    direction: CrossDirection::Bearish
+                },
+                MarketEventKind::SessionLow
+            ]
+        );
+    }
+
+    #[test]
+    fn round_numbers_use_the_price_magnitude() {
+        let mut detector = EventDetector::new(EventConfig::default());
+        detector.on_tick(&tick(0, 64_950.0, 1.0));
+        detector.on_tick(&tick(1, 64_990.0, 1.0));
+        detector.on_tick(&tick(2, 65_020.0, 1.0));
+        assert_eq!(
+            kinds(&mut detector),
+            [MarketEventKind::RoundNumber {
+                level: 65_000.0,
+                direction: CrossDirection::Bullish
+            }]
+        );
+    }
+
+    #[test]
+    fn round_step_follows_the_price_across_a_power_of_ten() {
+        let mut detector = EventDetector::new(EventConfig {
+            cooldown: Duration::zero(),
+            ..EventConfig::default()
+        });
+        let crossings = |detector: &mut EventDetector| -> Vec<f64> {
+            kinds(detector)
+                .into_iter()
+                .filter_map(|kind| match kind {
+                    MarketEventKind::RoundNumber { level, .. } => Some(level),
+                    _ => None,
+                })
+                .collect()
+        };
+        detector.on_tick(&tick(0, 9_950.0, 1.0));
+        detector.on_tick(&tick(1, 10_050.0, 1.0));
+        assert_eq!(crossings(&mut detector), [10_000.0]);
+        // Above 10k the levels are 1000 apart, not 100.
+        detector.on_tick(&tick(2, 10_150.0, 1.0));
+        assert!(crossings(&mut detector).is_empty());
+        detector.on_tick(&tick(3, 11_050.0, 1.0));
+        assert_eq!(crossings(&mut detector), [11_000.0]);
+        // And narrow again on the way back down.
+        detector.on_tick(&tick(4, 9_990.0, 1.0));
+        detector.on_tick(&tick(5, 9_890.0, 1.0));
+        assert_eq!(crossings(&mut detector), [10_000.0, 9_900.0]);
+    }
+
+    #[test]
+    fn rsi_levels_cool_down_independently() {
+        let mut detector = EventDetector::new(EventConfig {
+            cooldown: Duration::minutes(10),
+            ..EventConfig::default()
+        });
+        detector.on_bar(&bar(0, 10.0), 35.0);
+        detector.on_bar(&bar(1, 10.0), 25.0);
+        // A violent reversal through both levels within the cooldown.
+        detector.on_bar(&bar(2, 10.0), 75.0);
+        detector.on_bar(&bar(3, 10.0), 65.0);
+        let crossings: Vec<(f64, CrossDirection)> = kinds(&mut detector)
+            .into_iter()
+            .filter_map(|kind| match kind {
+                MarketEventKind::RsiCross { level, direction } => Some((level, direction)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            crossings,
+            [
+                (30.0, CrossDirection::Bearish),
+                (70.0, CrossDirection::Bullish)
+            ]
+        );
+    }
+
+    #[test]
+    fn volume_spikes_and_rsi_crossings_fire_on_bars() {
+        let mut detector = EventDetector::new(EventConfig::default());
+        for idx in 0..30 {
+            detector.on_bar(&bar(idx, 10.0 + (idx % 3) as f64), 50.0);
+        }
+        assert!(kinds(&mut detector).is_empty());
+
+        detector.on_bar(&bar(30, 40.0), 72.0);
+        let events = kinds(&mut detector);
+        assert!(matches!(events[0], MarketEventKind::VolumeSpike { sigma, .. } if sigma > 3.0));
+        assert_eq!(
+            events[1],
+            MarketEventKind::RsiCross {
+                level: 70.0,
+                direction: CrossDirection::Bullish
+            }
+        );
+    }
+
+    #[test]
+    fn large_trades_and_reconnect_gaps() {
+        let mut detector = EventDetector::new(EventConfig {
+            round_step: Some(1_000.0),
+            ..EventConfig::default()
+        });
+        for secs in 0..60 {
+            detector.on_tick(&tick(secs, 100.0, 0.5));
+        }
+        detector.on_tick(&tick(60, 100.0, 20.0));
+        assert!(matches!(
+            kinds(&mut detector)[..],
+            [MarketEventKind::LargeTrade { volume, side: Some(TradeSide::Buy) }] if volume == 20.0
+        ));
+
+        detector.reconnected();
+        detector.on_tick(&tick(400, 101.0, 0.5));
+        assert!(matches!(
+            kinds(&mut detector)[..],
+            [MarketEventKind::ReconnectGap { gap_pct, gap_secs }] if gap_pct == 1.0 && gap_secs == 340.0
+        ));
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::VecDeque;
+
+use chrono::Duration;
+
+use crate::models::{PriceTick, SourceSpec, TickRejection};
+
+/// Scales a median absolute deviation to a standard-deviation equivalent.
+const MAD_SCALE: f64 = 1.4826;
+
+/// Bad-print guard in front of the indicators.
+///
+/// Source options (all optional):
+/// - `filter`: `false` disables the filter for the asset
+/// - `outlier_window`: recent accepted prices forming the band, default `50`
+/// - `outlier_k`: band half-width in scaled MADs, default `10`
+/// - `outlier_min_pct`: band floor as a percentage of the median, default `2`
+/// - `outlier_reset`: consecutive outliers accepted as a genuine move, default `5`
+/// - `outlier_gap_secs`: a gap this long between ticks restarts the band, default `300`
+#[derive(Debug, Clone)]
+pub struct FilterConfig {
+    pub enabled: bool,
+    pub window: usize,
+    pub mad_k: f64,
+    pub min_band_pct: f64,
+    pub reset_after: usize,
+    pub max_gap: Duration,
+}
+
+impl Default for FilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: 50,
+            mad_k: 10.0,
+            min_band_pct: 2.0,
+            reset_after: 5,
+            max_gap: Duration::seconds(300),
+        }
+    }
+}
+
+impl FilterConfig {
+    pub fn from_spec(spec: &SourceSpec) -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: spec.parse_option("filter").unwrap_or(defaults.enabled),
+            window: spec
+                .parse_option::<usize>("outlier_window")
+                .unwrap_or(defaults.window)
+                .max(3),
+            mad_k: spec.parse_option("outlier_k").unwrap_or(defaults.mad_k),
+            min_band_pct: spec
+                .parse_option("outlier_min_pct")
+                .unwrap_or(defaults.min_band_pct),
+            reset_after: spec
+                .parse_option("outlier_reset")
+                .unwrap_or(defaults.reset_after),
+            max_gap: spec
+                .parse_option("outlier_gap_secs")
+                .map(Duration::seconds)
+                .unwrap_or(defaults.max_gap),
+        }
+    }
+}
+
+/// Rejects ticks that would corrupt the indicators: non-positive or non-finite
+/// values, timestamps older than the last accepted tick, exact repeats of it,
+/// and prices outside a rolling median ± MAD band.
+///
+/// The band is only enforced once half the window is filled. A run of
+/// `reset_after` consecutive outliers is taken as a real move rather than bad
+/// prints, so the band restarts around the new level instead of locking out
+/// the feed.
+pub struct TickFilter {
+    config: FilterConfig,
+    prices: VecDeque<f64>,
+    last: Option<PriceTick>,
+    outliers: Vec<f64>,
+}
+
+impl TickFilter {
+    pub fn new(config: FilterConfig) -> Self {
+        Self {
+            prices: VecDeque::with_capacity(config.window),
+            config,
+            last: None,
+            outliers: Vec::new(),
+        }
+    }
+
+    pub fn check(&mut self, tick: &PriceTick) -> Result<(), TickRejection> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        if !tick.price.is_finite()
+            || tick.price <= 0.0
+            || !tick.volume.is_finite()
+            || tick.volume < 0.0
+        {
+            return Err(TickRejection::Invalid);
+        }
+        if let Some(last) = &self.last {
+            if tick.timestamp < last.timestamp {
+                return Err(TickRejection::OutOfOrder);
+            }
+            if is_duplicate(tick, last) {
+                return Err(TickRejection::Duplicate);
+            }
+            if tick.timestamp - last.timestamp > self.config.max_gap {
+                self.prices.clear();
+                self.outliers.clear();
+            }
+        }
+
+        if self.is_outlier(tick.price) {
+            self.outliers.push(tick.price);
+            if self.outliers.len() < self.config.reset_after.max(1) {
+                return Err(TickRejection::Outlier);
+            }
+            self.prices.clear();
+            self.prices.extend(self.outliers.drain(..));
+        } else {
+            self.outliers.clear();
+            if self.prices.len() == self.config.window {
+                self.prices.pop_front();
+            }
+            self.prices.push_back(tick.price);
+        }
+
+        self.last = Some(tick.clone());
+        Ok(())
+    }
+
+    fn is_outlier(&self, price: f64) -> bool {
+        if self.prices.len() < self.config.window / 2 {
+            return false;
+        }
+        let median = median(self.prices.iter().copied());
+        let mad = median_abs_deviation(self.prices.iter().copied(), median);
+        let band =
+            (self.config.mad_k * MAD_SCALE * mad).max(median * self.config.min_band_pct / 100.0);
+        (price - median).abs() > band
+    }
+}
+
+fn is_duplicate(tick: &PriceTick, last: &PriceTick) -> bool {
+    tick.timestamp == last.timestamp
+        && tick.price == last.price
+        && tick.volume == last.volume
+        && tick.side == last.side
+}
+
+fn median(values: impl Iterator<Item = f64>) -> f64 {
+    let mut sorted: Vec<f64> = values.collect();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+fn median_abs_deviation(values: impl Iterator<Item = f64>, median_value: f64) -> f64 {
+    median(values.map(|v| (v - median_value).abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::models::TradeSide;
+
+    fn tick(secs: i64, price: f64) -> PriceTick {
+        PriceTick {
+            symbol: "btcusdt".to_string(),
+            price,
+            volume: 1.0,
+            timestamp: Utc.timestamp_opt(1_700_000_000 + secs, 0).unwrap(),
+            side: Some(TradeSide::Buy),
+        }
+    }
+
+    fn warmed() -> TickFilter {
+        let mut filter = TickFilter::new(FilterConfig::default());
+        for i in 0..50 {
+            let price = 100.0 + (i % 5) as f64 * 0.01;
+            filter.check(&tick(i, price)).unwrap();
+        }
+        filter
+    }
+
+    #[test]
+    fn rejects_invalid_prices_and_volumes() {
+        let mut filter = TickFilter::new(FilterConfig::default());
+        for price in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(filter.check(&tick(0, price)), Err(TickRejection::Invalid));
+        }
+        let mut bad_volume = tick(0, 100.0);
+        bad_volume.volume = f64::NAN;
+        assert_eq!(filter.check(&bad_volume), Err(TickRejection::Invalid));
+    }
+
+    #[test]
+    fn rejects_out_of_order_and_duplicate_ticks() {
+        let mut filter = TickFilter::new(FilterConfig::default());
+        filter.check(&tick(10, 100.0)).unwrap();
+        assert_eq!(
+            filter.check(&tick(9, 100.0)),
+            Err(TickRejection::OutOfOrder)
+        );
+        assert_eq!(
+            filter.check(&tick(10, 100.0)),
+            Err(TickRejection::Duplicate)
+        );
+        // Same instant, different trade.
+        filter.check(&tick(10, 100.5)).unwrap();
+    }
+
+    #[test]
+    fn rejects_fat_finger_prints_outside_the_band() {
+        let mut filter = warmed();
+        assert_eq!(filter.check(&tick(60, 10.0)), Err(TickRejection::Outlier));
+        assert_eq!(
+            filter.check(&tick(61, 1_000.0)),
+            Err(TickRejection::Outlier)
+        );
+        filter.check(&tick(62, 100.5)).unwrap();
+    }
+
+    #[test]
+    fn sustained_move_resets_the_band() {
+        let mut filter = warmed();
+        for i in 0..4 {
+            assert_eq!(
+                filter.check(&tick(60 + i, 80.0)),
+                Err(TickRejection::Outlier)
+            );
+        }
+        filter.check(&tick(64, 80.0)).unwrap();
+        filter.check(&tick(65, 80.1)).unwrap();
+    }
+
+    #[test]
+    fn long_gap_restarts_the_band() {
+        let mut filter = warmed();
+        filter.check(&tick(3_600, 90.0)).unwrap();
+    }
+
+    #[test]
+    fn disabled_filter_passes_everything() {
+        let mut filter = TickFilter::new(FilterConfig {
+            enabled: false,
+            ..FilterConfig::default()
+        });
+        filter.check(&tick(0, 0.0)).unwrap();
+        filter.check(&tick(-5, f64::NAN)).unwrap();
+    }
+}
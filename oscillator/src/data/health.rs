@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+
+use crate::{
+    data::backoff::Backoff,
+    models::{FeedState, FeedStatus, RejectionCounts, TickRejection},
+};
+
+/// Tick rate is recomputed over windows of roughly this length.
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+struct FeedHealth {
+    state: FeedState,
+    stale_after: Duration,
+    /// When the current state was entered; stands in for the last tick before the first one.
+    state_since: Instant,
+    last_tick: Option<(Instant, DateTime<Utc>)>,
+    reconnects: u64,
+    errors: u64,
+    last_error: Option<String>,
+    rejected: RejectionCounts,
+    dropped: u64,
+    window_start: Instant,
+    window_ticks: u64,
+    tick_rate: f64,
+}
+
+impl FeedHealth {
+    fn set_state(&mut self, state: FeedState) {
+        if self.state != state {
+            self.state = state;
+            self.state_since = Instant::now();
+        }
+    }
+
+    fn tick_rate(&self) -> f64 {
+        // A window that has run long without closing means the feed went quiet.
+        let elapsed = self.window_start.elapsed();
+        if elapsed >= RATE_WINDOW {
+            self.window_ticks as f64 / elapsed.as_secs_f64()
+        } else {
+            self.tick_rate
+        }
+    }
+
+    fn is_stale(&self) -> bool {
+        let quiet_since = match self.last_tick {
+            Some((at, _)) => at.max(self.state_since),
+            None => self.state_since,
+        };
+        quiet_since.elapsed() > self.stale_after
+    }
+
+    fn status(&self, (source, symbol): &FeedKey) -> FeedStatus {
+        let degraded = match self.state {
+            FeedState::Connecting | FeedState::Connected => self.is_stale(),
+            FeedState::Paused => false,
+            FeedState::Disconnected | FeedState::CircuitOpen => true,
+        };
+        FeedStatus {
+            symbol: symbol.clone(),
+            source: source.clone(),
+            state: self.state,
+            degraded,
+            last_tick: self.last_tick.map(|(_, at)| at),
+            last_tick_age_secs: self.last_tick.map(|(at, _)| at.elapsed().as_secs_f64()),
+            tick_rate: self.tick_rate(),
+            reconnects: self.reconnects,
+            errors: self.errors,
+            last_error: self.last_error.clone(),
+            rejected: self.rejected,
+            dropped: self.dropped,
+            stale_after_secs: self.stale_after.as_secs_f64(),
+        }
+    }
+}
+
+/// Feeds are tracked per source and symbol, so two sources carrying the same
+/// symbol never see each other's reconnects or errors.
+type FeedKey = (String, String);
+
+/// Per-asset feed health, written by sources and pipelines and read by the API.
+///
+/// Updates for feeds that were never registered are ignored, so sources can
+/// report unconditionally even when no store is wired up.
+#[derive(Debug, Clone, Default)]
+pub struct FeedHealthStore {
+    inner: Arc<RwLock<HashMap<FeedKey, FeedHealth>>>,
+}
+
+impl FeedHealthStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&self, source: &str, symbol: &str, stale_after: Duration) {
+        let now = Instant::now();
+        self.inner.write().insert(
+            key(source, symbol),
+            FeedHealth {
+                state: FeedState::Connecting,
+                stale_after,
+                state_since: now,
+                last_tick: None,
+                reconnects: 0,
+                errors: 0,
+                last_error: None,
+                rejected: RejectionCounts::default(),
+                dropped: 0,
+                window_start: now,
+                window_ticks: 0,
+                tick_rate: 0.0,
+            },
+        );
+    }
+
+    pub fn set_state(&self, source: &str, symbol: &str, state: FeedState) {
+        self.update(source, symbol, |feed| feed.set_state(state));
+    }
+
+    /// A tick proves the feed is live, whatever state was last reported.
+    pub fn record_tick(&self, source: &str, symbol: &str) {
+        self.update(source, symbol, |feed| {
+            let now = Instant::now();
+            feed.set_state(FeedState::Connected);
+            feed.last_tick = Some((now, Utc::now()));
+            feed.window_ticks += 1;
+            let elapsed = now.duration_since(feed.window_start);
+            if elapsed >= RATE_WINDOW {
+                feed.tick_rate = feed.window_ticks as f64 / elapsed.as_secs_f64();
+                feed.window_start = now;
+                feed.window_ticks = 0;
+            }
+        });
+    }
+
+    pub fn record_error(&self, source: &str, symbol: &str, err: impl Display) {
+        let message = err.to_string();
+        self.update(source, symbol, |feed| {
+            feed.errors += 1;
+            feed.last_error = Some(message);
+        });
+    }
+
+    pub fn record_rejection(&self, source: &str, symbol: &str, reason: TickRejection) {
+        self.update(source, symbol, |feed| feed.rejected.record(reason));
+    }
+
+    /// A tick the source had to discard because the pipeline fell behind.
+    pub fn record_dropped(&self, source: &str, symbol: &str) {
+        self.update(source, symbol, |feed| feed.dropped += 1);
+    }
+
+    /// A failed connection now waiting out `backoff`, which may have opened its circuit.
+    pub fn record_backoff(&self, source: &str, symbol: &str, backoff: &Backoff) {
+        let state = if backoff.is_open() {
+            FeedState::CircuitOpen
+        } else {
+            FeedState::Disconnected
+        };
+        self.set_state(source, symbol, state);
+    }
+
+    pub fn record_reconnect(&self, source: &str, symbol: &str) {
+        self.update(source, symbol, |feed| {
+            feed.reconnects += 1;
+            feed.set_state(FeedState::Connecting);
+        });
+    }
+
+    /// Reconnects recorded for the feed so far.
+    pub fn reconnects(&self, source: &str, symbol: &str) -> u64 {
+        self.inner
+            .read()
+            .get(&key(source, symbol))
+            .map_or(0, |feed| feed.reconnects)
+    }
+
+    pub fn status(&self, source: &str, symbol: &str) -> Option<FeedStatus> {
+        self.inner
+            .read()
+            .get_key_value(&key(source, symbol))
+            .map(|(key, feed)| feed.status(key))
+    }
+
+    pub fn statuses(&self) -> Vec<FeedStatus> {
+        let mut statuses: Vec<FeedStatus> = self
+            .inner
+            .read()
+            .iter()
+            .map(|(key, feed)| feed.status(key))
+            .collect();
+        statuses.sort_by(|a, b| (&a.symbol, &a.source).cmp(&(&b.symbol, &b.source)));
+        statuses
+    }
+
+    fn update(&self, source: &str, symbol: &str, apply: impl FnOnce(&mut FeedHealth)) {
+        if let Some(feed) = self.inner.write().get_mut(&key(source, symbol)) {
+            apply(feed);
+        }
+    }
+}
+
+fn key(source: &str, symbol: &str) -> FeedKey {
+    (source.to_string(), symbol.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::backoff::ReconnectPolicy;
+
+    fn store(stale_after: Duration) -> FeedHealthStore {
+        let store = FeedHealthStore::new();
+        store.register("binance", "btcusdt", stale_after);
+        store
+    }
+
+    fn state(store: &FeedHealthStore) -> FeedState {
+        store.status("binance", "btcusdt").unwrap().state
+    }
+
+    #[test]
+    fn feeds_move_through_connect_fail_and_reconnect() {
+        let store = store(Duration::from_secs(60));
+        assert_eq!(state(&store), FeedState::Connecting);
+        store.record_tick("binance", "btcusdt");
+        assert_eq!(state(&store), FeedState::Connected);
+
+        let mut backoff = ReconnectPolicy {
+            open_after: 2,
+            ..ReconnectPolicy::default()
+        }
+        .backoff();
+        store.record_error("binance", "btcusdt", "connection reset");
+        backoff.failure();
+        store.record_backoff("binance", "btcusdt", &backoff);
+        assert_eq!(state(&store), FeedState::Disconnected);
+        backoff.failure();
+        store.record_backoff("binance", "btcusdt", &backoff);
+        assert_eq!(state(&store), FeedState::CircuitOpen);
+
+        store.record_reconnect("binance", "btcusdt");
+        let status = store.status("binance", "btcusdt").unwrap();
+        assert_eq!(status.state, FeedState::Connecting);
+        assert_eq!(status.reconnects, 1);
+        assert_eq!(status.errors, 1);
+        assert_eq!(status.last_error.as_deref(), Some("connection reset"));
+        assert!(!status.degraded);
+    }
+
+    #[test]
+    fn disconnected_feeds_are_degraded_and_paused_ones_are_not() {
+        let store = store(Duration::from_secs(60));
+        store.set_state("binance", "btcusdt", FeedState::Disconnected);
+        assert!(store.status("binance", "btcusdt").unwrap().degraded);
+        store.set_state("binance", "btcusdt", FeedState::Paused);
+        assert!(!store.status("binance", "btcusdt").unwrap().degraded);
+    }
+
+    #[test]
+    fn silent_feeds_go_stale_until_the_next_tick() {
+        let store = store(Duration::from_millis(20));
+        store.record_tick("binance", "btcusdt");
+        assert!(!store.status("binance", "btcusdt").unwrap().degraded);
+
+        std::thread::sleep(Duration::from_millis(40));
+        let status = store.status("binance", "btcusdt").unwrap();
+        assert_eq!(status.state, FeedState::Connected);
+        assert!(status.degraded);
+        // Paused feeds are expected to be quiet.
+        store.set_state("binance", "btcusdt", FeedState::Paused);
+        assert!(!store.status("binance", "btcusdt").unwrap().degraded);
+
+        store.record_tick("binance", "btcusdt");
+        assert!(!store.status("binance", "btcusdt").unwrap().degraded);
+    }
+
+    #[test]
+    fn feeds_are_kept_apart_per_source() {
+        let store = store(Duration::from_secs(60));
+        store.register("replay", "btcusdt", Duration::from_secs(60));
+        store.record_reconnect("binance", "btcusdt");
+        store.record_dropped("binance", "btcusdt");
+
+        assert_eq!(store.reconnects("binance", "btcusdt"), 1);
+        assert_eq!(store.reconnects("replay", "btcusdt"), 0);
+        assert_eq!(store.status("replay", "btcusdt").unwrap().dropped, 0);
+        // Unregistered feeds are ignored.
+        store.record_tick("kraken", "btcusdt");
+        assert!(store.status("kraken", "btcusdt").is_none());
+
+        let sources: Vec<String> = store.statuses().into_iter().map(|s| s.source).collect();
+        assert_eq!(sources, ["binance", "replay"]);
+    }
+}
@@ -1,78 +1,1170 @@
-use std::collections::VecDeque;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    str::FromStr,
+};
 
-use crate::models::{MarketMetrics, PriceTick};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
 
+use crate::{
+    data::{
+        anchor::{ChangeAnchor, PriceAnchor},
+        bars::BarBuilder,
+        events::{EventConfig, EventDetector},
+        normalize::{MetricNormalizer, NormalizerSample},
+        regime::{RegimeClassifier, RegimeConfig},
+        signals::{Indicator, IndicatorValues},
+    },
+    models::{
+        Bar, BollingerMetrics, CrossDirection, EmaMetrics, MacdMetrics, MarketEvent, MarketMetrics,
+        PriceTick, SourceSpec, TimeframeMetrics, TradeFlowMetrics, VwapMetrics,
+    },
+};
+
+/// Bar length used unless the asset or `OSC_BAR_SECS` picks another.
+pub const DEFAULT_BAR_SECS: i64 = 60;
+/// Longer timeframes tracked unless an asset sets `timeframes`.
+pub const DEFAULT_TIMEFRAMES: &str = "15m,1h";
+
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0;
+
+/// How RSI averages gains and losses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RsiMode {
+    /// Plain sums over the last `period` changes; the original behaviour.
+    #[default]
+    Simple,
+    /// Wilder's smoothing: seeded with the first `period` changes, then
+    /// `avg = (avg * (period - 1) + change) / period`.
+    Wilder,
+}
+
+impl FromStr for RsiMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "simple" | "legacy" => Ok(Self::Simple),
+            "wilder" => Ok(Self::Wilder),
+            other => Err(anyhow::anyhow!("unknown RSI mode {other}")),
+        }
+    }
+}
+
+/// How the volatility metric is scaled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VolatilityMode {
+    /// Standard deviation of per-bar returns × 100, clamped to `[0, 10]`; the
+    /// original behaviour.
+    #[default]
+    PerBar,
+    /// Realized volatility of log returns normalized by the time between bar
+    /// closes and annualized, in percent (e.g. `60` = 60% a year).
+    Annualized,
+}
+
+impl FromStr for VolatilityMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "per_bar" | "legacy" => Ok(Self::PerBar),
+            "annualized" => Ok(Self::Annualized),
+            other => Err(anyhow::anyhow!("unknown volatility mode {other}")),
+        }
+    }
+}
+
+/// Fixed-capacity window with running sums, so means and variances update in O(1).
+///
+/// The sums are rebuilt from the stored values once per window length to keep
+/// floating-point drift from accumulating.
+#[derive(Debug, Clone)]
+pub(crate) struct RollingWindow {
+    values: VecDeque<f64>,
+    capacity: usize,
+    sum: f64,
+    sum_sq: f64,
+    pushes: usize,
+}
+
+impl RollingWindow {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            values: VecDeque::with_capacity(capacity),
+            capacity,
+            sum: 0.0,
+            sum_sq: 0.0,
+            pushes: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, value: f64) {
+        if self.values.len() == self.capacity
+            && let Some(old) = self.values.pop_front()
+        {
+            self.sum -= old;
+            self.sum_sq -= old * old;
+        }
+        self.values.push_back(value);
+        self.sum += value;
+        self.sum_sq += value * value;
+
+        self.pushes += 1;
+        if self.pushes >= self.capacity {
+            self.pushes = 0;
+            self.sum = self.values.iter().sum();
+            self.sum_sq = self.values.iter().map(|v| v * v).sum();
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Whether the window holds `capacity` values.
+    pub(crate) fn is_full(&self) -> bool {
+        self.values.len() == self.capacity
+    }
+
+    /// Oldest value still in the window.
+    pub(crate) fn first(&self) -> Option<f64> {
+        self.values.front().copied()
+    }
+
+    pub(crate) fn last(&self) -> Option<f64> {
+        self.values.back().copied()
+    }
+
+    fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub(crate) fn mean(&self) -> f64 {
+        if self.values.is_empty() {
+            0.0
+        } else {
+            self.sum / self.values.len() as f64
+        }
+    }
+
+    /// Population variance.
+    fn variance(&self) -> f64 {
+        if self.values.is_empty() {
+            return 0.0;
+        }
+        let mean = self.mean();
+        (self.sum_sq / self.values.len() as f64 - mean * mean).max(0.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Rsi {
+    mode: RsiMode,
+    period: usize,
+    /// Last `period` gains and losses, for `Simple`.
+    gains: RollingWindow,
+    losses: RollingWindow,
+    /// Smoothed averages and the number of changes seen, for `Wilder`.
+    avg_gain: f64,
+    avg_loss: f64,
+    changes: usize,
+}
+
+impl Rsi {
+    fn new(mode: RsiMode, period: usize) -> Self {
+        let period = period.max(1);
+        Self {
+            mode,
+            period,
+            gains: RollingWindow::new(period),
+            losses: RollingWindow::new(period),
+            avg_gain: 0.0,
+            avg_loss: 0.0,
+            changes: 0,
+        }
+    }
+
+    fn push(&mut self, change: f64) {
+        let gain = change.max(0.0);
+        let loss = (-change).max(0.0);
+        match self.mode {
+            RsiMode::Simple => {
+                self.gains.push(gain);
+                self.losses.push(loss);
+            }
+            RsiMode::Wilder => {
+                self.changes += 1;
+                let period = self.period as f64;
+                if self.changes <= self.period {
+                    // Seed with the simple average of the first `period` changes.
+                    self.avg_gain += gain / period;
+                    self.avg_loss += loss / period;
+                } else {
+                    self.avg_gain = (self.avg_gain * (period - 1.0) + gain) / period;
+                    self.avg_loss = (self.avg_loss * (period - 1.0) + loss) / period;
+                }
+            }
+        }
+    }
+
+    fn value(&self) -> f64 {
+        match self.mode {
+            RsiMode::Simple => {
+                if self.gains.len() < self.period {
+                    return 50.0;
+                }
+                let rs = self.gains.sum().max(0.0) / self.losses.sum().max(1e-9);
+                100.0 - (100.0 / (1.0 + rs))
+            }
+            RsiMode::Wilder => {
+                if self.changes < self.period {
+                    return 50.0;
+                }
+                if self.avg_loss <= f64::EPSILON {
+                    return if self.avg_gain <= f64::EPSILON {
+                        50.0
+                    } else {
+                        100.0
+                    };
+                }
+                100.0 - (100.0 / (1.0 + self.avg_gain / self.avg_loss))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Volatility {
+    mode: VolatilityMode,
+    /// Simple returns, for `PerBar`.
+    returns: RollingWindow,
+    /// Squared log returns and the years they span, for `Annualized`.
+    squared_log_returns: RollingWindow,
+    spans: RollingWindow,
+}
+
+impl Volatility {
+    fn new(mode: VolatilityMode, window: usize) -> Self {
+        Self {
+            mode,
+            returns: RollingWindow::new(window),
+            squared_log_returns: RollingWindow::new(window),
+            spans: RollingWindow::new(window),
+        }
+    }
+
+    fn push(&mut self, prev: f64, close: f64, span: Duration) {
+        match self.mode {
+            VolatilityMode::PerBar => {
+                self.returns.push(((close / prev) - 1.0).clamp(-1.0, 1.0));
+            }
+            VolatilityMode::Annualized => {
+                let years = span.num_milliseconds().max(1) as f64 / 1000.0 / SECONDS_PER_YEAR;
+                self.squared_log_returns.push((close / prev).ln().powi(2));
+                self.spans.push(years);
+            }
+        }
+    }
+
+    fn value(&self) -> f64 {
+        match self.mode {
+            VolatilityMode::PerBar => {
+                if self.returns.is_empty() {
+                    return 0.0;
+                }
+                (self.returns.variance().sqrt() * 100.0).clamp(0.0, 10.0)
+            }
+            VolatilityMode::Annualized => {
+                let years = self.spans.sum();
+                if self.spans.is_empty() || years <= 0.0 {
+                    return 0.0;
+                }
+                (self.squared_log_returns.sum().max(0.0) / years).sqrt() * 100.0
+            }
+        }
+    }
+}
+
+/// Periods for the trend and band indicators.
+///
+/// Source options (all optional, comma-separated periods):
+/// - `macd`: fast, slow and signal EMA periods, default `12,26,9`
+/// - `bollinger`: period and band width in deviations, default `20,2`
+/// - `ema`: fast and slow EMA periods for crossovers, default `9,21`
+/// - `atr`: Wilder-smoothed true range period, default `14`
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndicatorPeriods {
+    pub macd: (usize, usize, usize),
+    pub bollinger: (usize, f64),
+    pub ema: (usize, usize),
+    pub atr: usize,
+}
+
+impl Default for IndicatorPeriods {
+    fn default() -> Self {
+        Self {
+            macd: (12, 26, 9),
+            bollinger: (20, 2.0),
+            ema: (9, 21),
+            atr: 14,
+        }
+    }
+}
+
+impl IndicatorPeriods {
+    pub fn from_spec(spec: &SourceSpec) -> Self {
+        let defaults = Self::default();
+        let list = |key: &str| -> Vec<f64> {
+            spec.option(key)
+                .map(|raw| {
+                    raw.split(',')
+                        .filter_map(|part| part.trim().parse().ok())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let period =
+            |value: Option<&f64>, default: usize| value.map_or(default, |v| (*v as usize).max(1));
+
+        let macd = list("macd");
+        let bollinger = list("bollinger");
+        let ema = list("ema");
+        Self {
+            macd: (
+                period(macd.first(), defaults.macd.0),
+                period(macd.get(1), defaults.macd.1),
+                period(macd.get(2), defaults.macd.2),
+            ),
+            bollinger: (
+                period(bollinger.first(), defaults.bollinger.0),
+                bollinger.get(1).copied().unwrap_or(defaults.bollinger.1),
+            ),
+            ema: (
+                period(ema.first(), defaults.ema.0),
+                period(ema.get(1), defaults.ema.1),
+            ),
+            atr: spec.parse_option("atr").unwrap_or(defaults.atr).max(1),
+        }
+    }
+}
+
+/// Exponential moving average seeded with the simple average of its first
+/// `period` values.
+#[derive(Debug, Clone)]
+pub(crate) struct Ema {
+    period: usize,
+    count: usize,
+    value: f64,
+}
+
+impl Ema {
+    pub(crate) fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            count: 0,
+            value: 0.0,
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        self.count += 1;
+        if self.count <= self.period {
+            self.value += (value - self.value) / self.count as f64;
+        } else {
+            let alpha = 2.0 / (self.period as f64 + 1.0);
+            self.value += alpha * (value - self.value);
+        }
+    }
+
+    fn value(&self) -> Option<f64> {
+        (self.count >= self.period).then_some(self.value)
+    }
+}
+
+/// EMA of bar closes.
+impl Indicator for Ema {
+    fn on_bar(&mut self, bar: &Bar) {
+        self.push(bar.close);
+    }
+
+    fn values(&self, out: &mut IndicatorValues<'_>) {
+        if let Some(value) = self.value() {
+            out.set(value);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+}
+
+impl Macd {
+    pub(crate) fn new((fast, slow, signal): (usize, usize, usize)) -> Self {
+        Self {
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            signal: Ema::new(signal),
+        }
+    }
+
+    fn push(&mut self, close: f64) {
+        self.fast.push(close);
+        self.slow.push(close);
+        if let (Some(fast), Some(slow)) = (self.fast.value(), self.slow.value()) {
+            self.signal.push(fast - slow);
+        }
+    }
+
+    fn metrics(&self) -> Option<MacdMetrics> {
+        let line = self.fast.value()? - self.slow.value()?;
+        let signal = self.signal.value()?;
+        Some(MacdMetrics {
+            line,
+            signal,
+            histogram: line - signal,
+        })
+    }
+}
+
+/// MACD line, with `signal` and `histogram` fields.
+impl Indicator for Macd {
+    fn on_bar(&mut self, bar: &Bar) {
+        self.push(bar.close);
+    }
+
+    fn values(&self, out: &mut IndicatorValues<'_>) {
+        if let Some(macd) = self.metrics() {
+            out.set(macd.line);
+            out.set_field("signal", macd.signal);
+            out.set_field("histogram", macd.histogram);
+        }
+    }
+}
+
+/// Bandwidths remembered to decide whether the bands are unusually tight.
+const SQUEEZE_LOOKBACK: usize = 120;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Bollinger {
+    closes: RollingWindow,
+    period: usize,
+    width: f64,
+    bandwidths: RollingWindow,
+}
+
+impl Bollinger {
+    pub(crate) fn new((period, width): (usize, f64)) -> Self {
+        Self {
+            closes: RollingWindow::new(period),
+            period: period.max(1),
+            width,
+            bandwidths: RollingWindow::new(SQUEEZE_LOOKBACK),
+        }
+    }
+
+    fn push(&mut self, close: f64) {
+        self.closes.push(close);
+        if let Some((middle, upper, lower)) = self.bands() {
+            self.bandwidths.push(bandwidth(middle, upper, lower));
+        }
+    }
+
+    fn bands(&self) -> Option<(f64, f64, f64)> {
+        if self.closes.len() < self.period {
+            return None;
+        }
+        let middle = self.closes.mean();
+        let offset = self.width * self.closes.variance().sqrt();
+        Some((middle, middle + offset, middle - offset))
+    }
+
+    fn metrics(&self, price: f64) -> Option<BollingerMetrics> {
+        let (middle, upper, lower) = self.bands()?;
+        let width = upper - lower;
+        let bandwidth = bandwidth(middle, upper, lower);
+        let squeeze = self.bandwidths.len() >= self.period
+            && bandwidth < self.bandwidths.mean() - self.bandwidths.variance().sqrt();
+        Some(BollingerMetrics {
+            middle,
+            upper,
+            lower,
+            percent_b: if width > 0.0 {
+                (price - lower) / width
+            } else {
+                0.5
+            },
+            bandwidth,
+            squeeze,
+        })
+    }
+}
+
+/// Middle band, with `upper`, `lower`, `percent_b` of the last close,
+/// `bandwidth` and `squeeze` (`1` while squeezed) fields.
+impl Indicator for Bollinger {
+    fn on_bar(&mut self, bar: &Bar) {
+        self.push(bar.close);
+    }
+
+    fn values(&self, out: &mut IndicatorValues<'_>) {
+        let Some(bands) = self.closes.last().and_then(|close| self.metrics(close)) else {
+            return;
+        };
+        out.set(bands.middle);
+        out.set_field("upper", bands.upper);
+        out.set_field("lower", bands.lower);
+        out.set_field("percent_b", bands.percent_b);
+        out.set_field("bandwidth", bands.bandwidth);
+        out.set_field("squeeze", if bands.squeeze { 1.0 } else { 0.0 });
+    }
+}
+
+fn bandwidth(middle: f64, upper: f64, lower: f64) -> f64 {
+    if middle > 0.0 {
+        (upper - lower) / middle
+    } else {
+        0.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct EmaCross {
+    fast: Ema,
+    slow: Ema,
+    cross: Option<CrossDirection>,
+}
+
+impl EmaCross {
+    pub(crate) fn new((fast, slow): (usize, usize)) -> Self {
+        Self {
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            cross: None,
+        }
+    }
+
+    fn push(&mut self, close: f64) {
+        let before = self.spread();
+        self.fast.push(close);
+        self.slow.push(close);
+        self.cross = match (before, self.spread()) {
+            (Some(before), Some(after)) if before <= 0.0 && after > 0.0 => {
+                Some(CrossDirection::Bullish)
+            }
+            (Some(before), Some(after)) if before >= 0.0 && after < 0.0 => {
+                Some(CrossDirection::Bearish)
+            }
+            _ => None,
+        };
+    }
+
+    fn spread(&self) -> Option<f64> {
+        Some(self.fast.value()? - self.slow.value()?)
+    }
+
+    fn metrics(&self) -> Option<EmaMetrics> {
+        Some(EmaMetrics {
+            fast: self.fast.value()?,
+            slow: self.slow.value()?,
+            cross: self.cross,
+        })
+    }
+}
+
+/// Fast minus slow EMA, with `fast`, `slow` and `cross` (`1` on a bullish
+/// cross, `-1` on a bearish one, `0` otherwise) fields.
+impl Indicator for EmaCross {
+    fn on_bar(&mut self, bar: &Bar) {
+        self.push(bar.close);
+    }
+
+    fn values(&self, out: &mut IndicatorValues<'_>) {
+        let Some(ema) = self.metrics() else {
+            return;
+        };
+        out.set(ema.fast - ema.slow);
+        out.set_field("fast", ema.fast);
+        out.set_field("slow", ema.slow);
+        out.set_field(
+            "cross",
+            match ema.cross {
+                Some(CrossDirection::Bullish) => 1.0,
+                Some(CrossDirection::Bearish) => -1.0,
+                None => 0.0,
+            },
+        );
+    }
+}
+
+/// Average true range with Wilder's smoothing.
+#[derive(Debug, Clone)]
+pub(crate) struct Atr {
+    period: usize,
+    count: usize,
+    value: f64,
+    prev_close: Option<f64>,
+}
+
+impl Atr {
+    pub(crate) fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            count: 0,
+            value: 0.0,
+            prev_close: None,
+        }
+    }
+
+    fn push(&mut self, bar: &Bar) {
+        let range = match self.prev_close {
+            Some(prev) => (bar.high - bar.low)
+                .max((bar.high - prev).abs())
+                .max((bar.low - prev).abs()),
+            None => bar.high - bar.low,
+        };
+        self.prev_close = Some(bar.close);
+        self.count += 1;
+        if self.count <= self.period {
+            self.value += (range - self.value) / self.count as f64;
+        } else {
+            let period = self.period as f64;
+            self.value = (self.value * (period - 1.0) + range) / period;
+        }
+    }
+
+    fn value(&self) -> Option<f64> {
+        (self.count >= self.period).then_some(self.value)
+    }
+}
+
+impl Indicator for Atr {
+    fn on_bar(&mut self, bar: &Bar) {
+        self.push(bar);
+    }
+
+    fn values(&self, out: &mut IndicatorValues<'_>) {
+        if let Some(atr) = self.value() {
+            out.set(atr);
+        }
+    }
+}
+
+/// Session VWAP over bar typical prices, restarting at local midnight in `timezone`.
+#[derive(Debug, Clone)]
+pub(crate) struct Vwap {
+    timezone: Tz,
+    day: Option<NaiveDate>,
+    price_volume: f64,
+    volume: f64,
+    last_close: Option<f64>,
+}
+
+impl Vwap {
+    pub(crate) fn new(timezone: Tz) -> Self {
+        Self {
+            timezone,
+            day: None,
+            price_volume: 0.0,
+            volume: 0.0,
+            last_close: None,
+        }
+    }
+
+    fn push(&mut self, bar: &Bar) {
+        let day = bar.start.with_timezone(&self.timezone).date_naive();
+        if self.day != Some(day) {
+            self.day = Some(day);
+            self.price_volume = 0.0;
+            self.volume = 0.0;
+        }
+        let typical = (bar.high + bar.low + bar.close) / 3.0;
+        self.price_volume += typical * bar.volume;
+        self.volume += bar.volume;
+        self.last_close = Some(bar.close);
+    }
+
+    fn metrics(&self, price: f64) -> Option<VwapMetrics> {
+        if self.volume <= 0.0 {
+            return None;
+        }
+        let vwap = self.price_volume / self.volume;
+        Some(VwapMetrics {
+            vwap,
+            deviation_pct: (price - vwap) / vwap * 100.0,
+        })
+    }
+}
+
+/// Session VWAP, with the last close's `deviation_pct` from it.
+impl Indicator for Vwap {
+    fn on_bar(&mut self, bar: &Bar) {
+        self.push(bar);
+    }
+
+    fn values(&self, out: &mut IndicatorValues<'_>) {
+        let Some(vwap) = self.last_close.and_then(|close| self.metrics(close)) else {
+            return;
+        };
+        out.set(vwap.vwap);
+        out.set_field("deviation_pct", vwap.deviation_pct);
+    }
+}
+
+/// A bar length written as a count and a unit: `30s`, `15m`, `1h` or `1d`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timeframe(Duration);
+
+impl Timeframe {
+    pub fn duration(&self) -> Duration {
+        self.0
+    }
+
+    /// Parses a comma list such as `15m,1h`, skipping entries that do not parse.
+    pub fn parse_list(raw: &str) -> Vec<Self> {
+        raw.split(',')
+            .filter_map(|part| part.trim().parse().ok())
+            .collect()
+    }
+}
+
+impl FromStr for Timeframe {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (count, unit) = s.split_at(split);
+        let count: i64 = count.parse()?;
+        anyhow::ensure!(count > 0, "timeframe must be positive");
+        let duration = match unit {
+            "s" => Duration::seconds(count),
+            "m" => Duration::minutes(count),
+            "h" => Duration::hours(count),
+            "d" => Duration::days(count),
+            other => return Err(anyhow::anyhow!("unknown timeframe unit {other:?}")),
+        };
+        Ok(Self(duration))
+    }
+}
+
+impl std::fmt::Display for Timeframe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let secs = self.0.num_seconds();
+        if secs % 86_400 == 0 {
+            write!(f, "{}d", secs / 86_400)
+        } else if secs % 3_600 == 0 {
+            write!(f, "{}h", secs / 3_600)
+        } else if secs % 60 == 0 {
+            write!(f, "{}m", secs / 60)
+        } else {
+            write!(f, "{secs}s")
+        }
+    }
+}
+
+/// RSI, volatility, MACD, the EMA pair and the regime on one longer timeframe,
+/// aggregated from the same ticks as the primary bars.
+#[derive(Debug, Clone)]
+struct TimeframeIndicators {
+    timeframe: Timeframe,
+    bars: BarBuilder,
+    rsi: Rsi,
+    volatility: Volatility,
+    macd: Macd,
+    ema: EmaCross,
+    regime: RegimeClassifier,
+    volumes: RollingWindow,
+    last_close: Option<(f64, DateTime<Utc>)>,
+}
+
+impl TimeframeIndicators {
+    fn push(&mut self, tick: &PriceTick) {
+        let Some(bar) = self.bars.push(tick) else {
+            return;
+        };
+        self.volumes.push(bar.volume);
+        self.macd.push(bar.close);
+        self.ema.push(bar.close);
+        if let Some((prev, prev_end)) = self.last_close {
+            self.rsi.push(bar.close - prev);
+            self.volatility.push(prev, bar.close, bar.end - prev_end);
+        }
+        self.last_close = Some((bar.close, bar.end));
+        let volume_ratio = match (self.volumes.last(), self.volumes.mean()) {
+            (Some(current), avg) if avg > 0.0 => (current / avg).clamp(0.1, 3.0),
+            _ => 1.0,
+        };
+        self.regime
+            .push(&bar, self.volatility.value(), volume_ratio);
+    }
+
+    fn metrics(&self, price: f64) -> TimeframeMetrics {
+        let price_change_percent = match self.last_close {
+            Some((close, _)) if close > 0.0 => (price - close) / close * 100.0,
+            _ => 0.0,
+        };
+        TimeframeMetrics {
+            timeframe: self.timeframe.to_string(),
+            bar_secs: self.timeframe.duration().num_seconds(),
+            price_change_percent,
+            rsi: self.rsi.value(),
+            volatility: self.volatility.value(),
+            macd: self.macd.metrics(),
+            ema: self.ema.metrics(),
+            regime: self.regime.current(),
+        }
+    }
+}
+
+/// Indicators over closed OHLCV bars rather than raw ticks, so an RSI(14)
+/// spans the same wall-clock time on a busy crypto pair as on a stock polled
+/// once a minute. Ticks still refresh the live price between bar closes.
+///
+/// Every built-in indicator updates incrementally in O(1) per bar; assets can
+/// add more through `with_indicator`.
 pub struct IndicatorCalculator {
     symbol: String,
-    period: usize,
     max_samples: usize,
-    prices: VecDeque<f64>,
-    volumes: VecDeque<f64>,
-    returns: VecDeque<f64>,
-    last_price: Option<f64>,
-    open_price: Option<f64>,
+    bars: BarBuilder,
+    rsi: Rsi,
+    volatility: Volatility,
+    macd: Macd,
+    bollinger: Bollinger,
+    ema: EmaCross,
+    atr: Atr,
+    vwap: Vwap,
+    regime: RegimeClassifier,
+    events: EventDetector,
+    normalizer: MetricNormalizer,
+    periods: IndicatorPeriods,
+    /// Timeframes asked for through `with_timeframes`, shortest first.
+    requested_timeframes: Vec<Timeframe>,
+    /// The requested timeframes longer than the primary bars.
+    timeframes: Vec<TimeframeIndicators>,
+    /// Pluggable indicators and the names they publish under.
+    custom: Vec<(String, Box<dyn Indicator>)>,
+    volumes: RollingWindow,
+    /// Close and end time of the previous closed bar.
+    last_close: Option<(f64, DateTime<Utc>)>,
+    /// Reference for `price_change_percent`.
+    anchor: PriceAnchor,
+    /// Aggressor volume per closed bar as `(buy, sell)`, with running window sums.
+    flow: VecDeque<(f64, f64)>,
+    buy_volume: f64,
+    sell_volume: f64,
+    cumulative_delta: f64,
 }
 
 impl IndicatorCalculator {
     pub fn new(symbol: impl Into<String>, period: usize, max_samples: usize) -> Self {
         Self {
             symbol: symbol.into(),
-            period,
             max_samples,
-            prices: VecDeque::with_capacity(max_samples),
-            volumes: VecDeque::with_capacity(max_samples),
-            returns: VecDeque::with_capacity(max_samples),
-            last_price: None,
-            open_price: None,
+            bars: BarBuilder::new(Duration::seconds(DEFAULT_BAR_SECS)),
+            rsi: Rsi::new(RsiMode::default(), period),
+            volatility: Volatility::new(VolatilityMode::default(), max_samples),
+            macd: Macd::new(IndicatorPeriods::default().macd),
+            bollinger: Bollinger::new(IndicatorPeriods::default().bollinger),
+            ema: EmaCross::new(IndicatorPeriods::default().ema),
+            atr: Atr::new(IndicatorPeriods::default().atr),
+            vwap: Vwap::new(Tz::UTC),
+            regime: RegimeClassifier::new(RegimeConfig::default()),
+            events: EventDetector::new(EventConfig::default()),
+            normalizer: MetricNormalizer::default(),
+            periods: IndicatorPeriods::default(),
+            requested_timeframes: Vec::new(),
+            timeframes: Vec::new(),
+            custom: Vec::new(),
+            volumes: RollingWindow::new(max_samples),
+            last_close: None,
+            anchor: PriceAnchor::new(ChangeAnchor::default(), None),
+            flow: VecDeque::with_capacity(max_samples),
+            buy_volume: 0.0,
+            sell_volume: 0.0,
+            cumulative_delta: 0.0,
         }
     }
 
-    pub fn on_tick(&mut self, tick: &PriceTick) -> MarketMetrics {
-        Self::push_sample(&mut self.prices, self.max_samples, tick.price);
-        Self::push_sample(&mut self.volumes, self.max_samples, tick.volume);
+    /// Aggregates ticks into bars of `duration` instead of the default minute.
+    pub fn with_bar_duration(mut self, duration: Duration) -> Self {
+        self.bars = BarBuilder::new(duration);
+        self.rebuild_timeframes();
+        self
+    }
+
+    pub fn with_rsi_mode(mut self, mode: RsiMode) -> Self {
+        self.rsi = Rsi::new(mode, self.rsi.period);
+        self.rebuild_timeframes();
+        self
+    }
+
+    pub fn with_volatility_mode(mut self, mode: VolatilityMode) -> Self {
+        self.volatility = Volatility::new(mode, self.max_samples);
+        self.rebuild_timeframes();
+        self
+    }
+
+    pub fn with_periods(mut self, periods: &IndicatorPeriods) -> Self {
+        self.macd = Macd::new(periods.macd);
+        self.bollinger = Bollinger::new(periods.bollinger);
+        self.ema = EmaCross::new(periods.ema);
+        self.atr = Atr::new(periods.atr);
+        self.periods = periods.clone();
+        self.rebuild_timeframes();
+        self
+    }
+
+    /// Also tracks RSI, volatility, MACD, the EMA pair and the regime on each
+    /// of `timeframes`, reported in `MarketMetrics::timeframes`. Timeframes no
+    /// longer than the primary bars are ignored, whichever of this and
+    /// `with_bar_duration` is called first.
+    pub fn with_timeframes(mut self, timeframes: &[Timeframe]) -> Self {
+        let mut timeframes = timeframes.to_vec();
+        timeframes.sort();
+        timeframes.dedup();
+        self.requested_timeframes = timeframes;
+        self.rebuild_timeframes();
+        self
+    }
+
+    /// Restarts the session VWAP at local midnight in `timezone` instead of UTC.
+    pub fn with_session_timezone(mut self, timezone: Tz) -> Self {
+        self.vwap = Vwap::new(timezone);
+        self.events.set_timezone(timezone);
+        self
+    }
+
+    pub fn with_regime(mut self, config: RegimeConfig) -> Self {
+        self.regime = RegimeClassifier::new(config);
+        self.rebuild_timeframes();
+        self
+    }
+
+    pub fn with_events(mut self, config: EventConfig) -> Self {
+        self.events = EventDetector::new(config);
+        self.events.set_timezone(self.vwap.timezone);
+        self
+    }
+
+    pub fn with_normalizer(mut self, normalizer: MetricNormalizer) -> Self {
+        self.normalizer = normalizer;
+        self
+    }
+
+    /// Measures `price_change_percent` against `anchor` instead of the price
+    /// 24 hours ago.
+    pub fn with_change_anchor(mut self, anchor: PriceAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Runs `indicator` alongside the built-ins, publishing its outputs in
+    /// `MarketMetrics::values` under `name`.
+    pub fn with_indicator(
+        mut self,
+        name: impl Into<String>,
+        indicator: Box<dyn Indicator>,
+    ) -> Self {
+        self.custom.push((name.into(), indicator));
+        self
+    }
 
-        if let Some(prev) = self.last_price {
-            let r = ((tick.price / prev) - 1.0).clamp(-1.0, 1.0);
-            Self::push_sample(&mut self.returns, self.max_samples, r);
+    fn timeframe_indicators(&self, timeframe: Timeframe) -> TimeframeIndicators {
+        TimeframeIndicators {
+            timeframe,
+            bars: BarBuilder::new(timeframe.duration()),
+            rsi: Rsi::new(self.rsi.mode, self.rsi.period),
+            volatility: Volatility::new(self.volatility.mode, self.max_samples),
+            macd: Macd::new(self.periods.macd),
+            ema: EmaCross::new(self.periods.ema),
+            regime: RegimeClassifier::new(self.regime.config().clone()),
+            volumes: RollingWindow::new(self.max_samples),
+            last_close: None,
         }
+    }
+
+    /// Keeps the longer timeframes on the same settings as the primary bars.
+    fn rebuild_timeframes(&mut self) {
+        self.timeframes = self
+            .requested_timeframes
+            .iter()
+            .filter(|timeframe| timeframe.duration() > self.bars.duration())
+            .map(|timeframe| self.timeframe_indicators(*timeframe))
+            .collect();
+    }
+
+    /// Length of the primary bars.
+    pub fn bar_duration(&self) -> Duration {
+        self.bars.duration()
+    }
+
+    /// Length of the longest timeframe tracked, or of the primary bars if none is.
+    pub fn longest_timeframe(&self) -> Duration {
+        self.timeframes.last().map_or_else(
+            || self.bars.duration(),
+            |timeframe| timeframe.timeframe.duration(),
+        )
+    }
+
+    /// Market events detected since the last call.
+    pub fn take_events(&mut self) -> Vec<MarketEvent> {
+        self.events.take()
+    }
 
-        self.last_price = Some(tick.price);
-        self.open_price.get_or_insert(tick.price);
+    /// Marks a feed reconnect so a price gap across it is reported.
+    pub fn reconnected(&mut self) {
+        self.events.reconnected();
+    }
+
+    /// Feeds historical ticks through the window and returns the metrics after
+    /// the last one. Events detected in the history are dropped.
+    pub fn warm_up(&mut self, history: &[PriceTick]) -> Option<MarketMetrics> {
+        let metrics = history.iter().map(|tick| self.on_tick(tick)).last();
+        self.events.take();
+        metrics
+    }
 
+    /// Adds a tick to the forming bar, updating the indicators whenever it
+    /// closes the previous one.
+    pub fn on_tick(&mut self, tick: &PriceTick) -> MarketMetrics {
+        if let Some(bar) = self.bars.push(tick) {
+            self.on_bar(&bar);
+        }
+        for (_, indicator) in &mut self.custom {
+            indicator.on_tick(tick);
+        }
+        for timeframe in &mut self.timeframes {
+            timeframe.push(tick);
+        }
+        self.events.on_tick(tick);
+        self.anchor.push(tick.timestamp, tick.price);
+
+        let sample = self.normalizer_sample(tick.price);
         MarketMetrics {
             symbol: self.symbol.clone(),
             price: tick.price,
-            price_change_percent: self.price_change_pct(),
-            volume: tick.volume,
-            volume_ratio: self.volume_ratio(),
-            rsi: self.rsi(),
-            volatility: self.volatility(),
+            price_change_percent: sample.price_change_percent,
+            volume: self.volumes.last().unwrap_or(0.0),
+            volume_ratio: sample.volume_ratio,
+            rsi: self.rsi.value(),
+            volatility: sample.volatility,
             tempo_bias: self.tempo_bias(),
             last_updated: tick.timestamp,
+            book: None,
+            flow: self.trade_flow(),
+            session: None,
+            macd: self.macd.metrics(),
+            bollinger: self.bollinger.metrics(tick.price),
+            ema: self.ema.metrics(),
+            atr: self.atr.value(),
+            vwap: self.vwap.metrics(tick.price),
+            regime: self.regime.current(),
+            values: self.custom_values(),
+            normalized: self.normalizer.normalize(&sample),
+            timeframes: self
+                .timeframes
+                .iter()
+                .map(|timeframe| timeframe.metrics(tick.price))
+                .collect(),
         }
     }
 
-    fn push_sample(deque: &mut VecDeque<f64>, max_samples: usize, value: f64) {
-        if deque.len() == max_samples {
-            deque.pop_front();
+    fn on_bar(&mut self, bar: &Bar) {
+        self.volumes.push(bar.volume);
+        let builtins: [&mut dyn Indicator; 5] = [
+            &mut self.macd,
+            &mut self.bollinger,
+            &mut self.ema,
+            &mut self.atr,
+            &mut self.vwap,
+        ];
+        let custom = self
+            .custom
+            .iter_mut()
+            .map(|(_, indicator)| &mut **indicator);
+        for indicator in builtins.into_iter().chain(custom) {
+            indicator.on_bar(bar);
         }
-        deque.push_back(value);
+
+        if let Some((prev, prev_end)) = self.last_close {
+            self.rsi.push(bar.close - prev);
+            self.volatility.push(prev, bar.close, bar.end - prev_end);
+        }
+        self.events.on_bar(bar, self.rsi.value());
+        self.last_close = Some((bar.close, bar.end));
+
+        if bar.buy_volume + bar.sell_volume > 0.0 {
+            self.push_flow(bar.buy_volume, bar.sell_volume);
+        }
+        self.regime
+            .push(bar, self.volatility.value(), self.volume_ratio());
+        self.normalizer.observe(&self.normalizer_sample(bar.close));
     }
 
-    fn price_change_pct(&self) -> f64 {
-        match (self.open_price, self.last_price) {
-            (Some(open), Some(last)) if open > 0.0 => ((last - open) / open) * 100.0,
-            _ => 0.0,
+    fn normalizer_sample(&self, price: f64) -> NormalizerSample {
+        NormalizerSample {
+            price,
+            price_change_percent: self.anchor.change_pct(price),
+            volatility: self.volatility.value(),
+            volume_ratio: self.volume_ratio(),
         }
     }
 
-    fn volume_ratio(&self) -> f64 {
-        if self.volumes.is_empty() {
-            return 1.0;
+    fn custom_values(&self) -> BTreeMap<String, f64> {
+        let mut values = BTreeMap::new();
+        for (name, indicator) in &self.custom {
+            indicator.values(&mut IndicatorValues::new(name, &mut values));
+        }
+        values
+    }
+
+    fn push_flow(&mut self, buy: f64, sell: f64) {
+        if self.flow.len() == self.max_samples
+            && let Some((old_buy, old_sell)) = self.flow.pop_front()
+        {
+            self.buy_volume -= old_buy;
+            self.sell_volume -= old_sell;
         }
+        self.buy_volume += buy;
+        self.sell_volume += sell;
+        self.cumulative_delta += buy - sell;
+        self.flow.push_back((buy, sell));
+    }
+
+    fn trade_flow(&self) -> Option<TradeFlowMetrics> {
+        if self.flow.is_empty() {
+            return None;
+        }
+        // Running sums drift slightly below zero from float error; clamp them.
+        let buy = self.buy_volume.max(0.0);
+        let sell = self.sell_volume.max(0.0);
+        let total = buy + sell;
+        Some(TradeFlowMetrics {
+            buy_volume: buy,
+            sell_volume: sell,
+            cumulative_delta: self.cumulative_delta,
+            imbalance: if total > 0.0 {
+                (buy - sell) / total
+            } else {
+                0.0
+            },
+        })
+    }
 
-        let current = *self.volumes.back().unwrap_or(&1.0);
-        let avg = self.volumes.iter().copied().sum::<f64>() / self.volumes.len() as f64;
+    fn volume_ratio(&self) -> f64 {
+        let Some(current) = self.volumes.last() else {
+            return 1.0;
+        };
+        let avg = self.volumes.mean();
         if avg == 0.0 {
             1.0
         } else {
@@ -80,49 +1172,316 @@ impl IndicatorCalculator {
         }
     }
 
-    fn rsi(&self) -> f64 {
-        if self.prices.len() < self.period + 1 {
-            return 50.0;
+    fn tempo_bias(&self) -> f64 {
+        (self.volume_ratio() - 1.0).clamp(-0.5, 0.5)
+    }
+}
+
+impl Default for IndicatorCalculator {
+    fn default() -> Self {
+        Self::new("UNKNOWN", 14, 256)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{data::signals::IndicatorRegistry, models::IndicatorSpec};
+
+    /// One tick per bar; each closes the bar before it.
+    fn feed(calc: &mut IndicatorCalculator, closes: &[f64]) -> MarketMetrics {
+        let start = Utc.timestamp_opt(1_700_000_040, 0).unwrap();
+        let ticks: Vec<PriceTick> = closes
+            .iter()
+            .enumerate()
+            .map(|(idx, &price)| PriceTick {
+                symbol: "btcusdt".to_string(),
+                price,
+                volume: 1.0,
+                timestamp: start + Duration::seconds(DEFAULT_BAR_SECS * idx as i64),
+                side: None,
+            })
+            .collect();
+        calc.warm_up(&ticks).expect("metrics")
+    }
+
+    #[test]
+    fn parses_and_labels_timeframes() {
+        let timeframes = Timeframe::parse_list("15m, 1h,90m,3600s,0m,5x");
+        let labels: Vec<String> = timeframes.iter().map(ToString::to_string).collect();
+        assert_eq!(labels, ["15m", "1h", "90m", "1h"]);
+        assert_eq!(timeframes[1].duration(), Duration::hours(1));
+    }
+
+    #[test]
+    fn tracks_longer_timeframes_side_by_side() {
+        let mut calc = IndicatorCalculator::new("btcusdt", 3, 64)
+            .with_timeframes(&Timeframe::parse_list("15m,5m,1m"))
+            .with_rsi_mode(RsiMode::Wilder);
+        let closes: Vec<f64> = (0..100).map(|idx| 100.0 + idx as f64).collect();
+        let metrics = feed(&mut calc, &closes);
+
+        // The primary one-minute bars are not repeated.
+        let labels: Vec<&str> = metrics
+            .timeframes
+            .iter()
+            .map(|timeframe| timeframe.timeframe.as_str())
+            .collect();
+        assert_eq!(labels, ["5m", "15m"]);
+        let (five, fifteen) = (&metrics.timeframes[0], &metrics.timeframes[1]);
+        assert_eq!(fifteen.bar_secs, 900);
+        assert_eq!(five.rsi, 100.0);
+        assert!(fifteen.price_change_percent > five.price_change_percent);
+        assert!(five.macd.is_none());
+    }
+
+    #[test]
+    fn timeframes_follow_the_primary_settings_in_any_order() {
+        let timeframes = Timeframe::parse_list("15m,1h");
+        let regime = RegimeConfig {
+            confirm_bars: 7,
+            ..RegimeConfig::default()
+        };
+        let labels = |calc: &IndicatorCalculator| -> Vec<String> {
+            calc.timeframes
+                .iter()
+                .map(|indicators| indicators.timeframe.to_string())
+                .collect()
+        };
+
+        let before = IndicatorCalculator::new("btcusdt", 14, 64)
+            .with_timeframes(&timeframes)
+            .with_bar_duration(Duration::minutes(30))
+            .with_regime(regime.clone());
+        let after = IndicatorCalculator::new("btcusdt", 14, 64)
+            .with_regime(regime)
+            .with_bar_duration(Duration::minutes(30))
+            .with_timeframes(&timeframes);
+        for calc in [&before, &after] {
+            assert_eq!(labels(calc), ["1h"]);
+            assert_eq!(calc.timeframes[0].regime.config().confirm_bars, 7);
         }
+    }
 
-        let mut gains = 0.0;
-        let mut losses = 0.0;
+    /// Wilder's 14-period RSI worked example from "New Concepts in Technical Trading Systems".
+    const WILDER_CLOSES: [f64; 16] = [
+        44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03, 45.61,
+        46.28, 46.28, 46.00,
+    ];
 
-        let len = self.prices.len();
-        let start = len.saturating_sub(self.period + 1);
-        let window_prices: Vec<f64> = self.prices.iter().skip(start).copied().collect();
-        for window in window_prices.windows(2) {
-            let change = window[1] - window[0];
-            if change > 0.0 {
-                gains += change;
-            } else {
-                losses -= change;
+    #[test]
+    fn simple_rsi_matches_window_sums() {
+        let mut calc = IndicatorCalculator::new("btcusdt", 3, 64);
+        // Closed bars 100, 102, 101, 104: gains 5, losses 1.
+        let metrics = feed(&mut calc, &[100.0, 102.0, 101.0, 104.0, 104.0]);
+        assert!((metrics.rsi - (100.0 - 100.0 / 6.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wilder_rsi_matches_reference_values() {
+        let mut calc = IndicatorCalculator::new("btcusdt", 14, 64).with_rsi_mode(RsiMode::Wilder);
+        // The last close only closes the bar before it, so 15 bars have closed.
+        let metrics = feed(&mut calc, &WILDER_CLOSES);
+        assert!((metrics.rsi - 70.46).abs() < 0.05, "{}", metrics.rsi);
+
+        let mut calc = IndicatorCalculator::new("btcusdt", 14, 64).with_rsi_mode(RsiMode::Wilder);
+        let mut closes = WILDER_CLOSES.to_vec();
+        closes.push(46.03);
+        let metrics = feed(&mut calc, &closes);
+        assert!((metrics.rsi - 66.25).abs() < 0.05, "{}", metrics.rsi);
+    }
+
+    #[test]
+    fn rsi_is_neutral_until_the_period_fills() {
+        let mut calc = IndicatorCalculator::new("btcusdt", 14, 64).with_rsi_mode(RsiMode::Wilder);
+        let metrics = feed(&mut calc, &WILDER_CLOSES[..10]);
+        assert_eq!(metrics.rsi, 50.0);
+    }
+
+    #[test]
+    fn annualized_volatility_normalizes_by_bar_time() {
+        // Alternating ±1% log moves every minute.
+        let closes: Vec<f64> = (0..200)
+            .map(|idx| {
+                if idx % 2 == 0 {
+                    100.0
+                } else {
+                    100.0 * 0.01f64.exp()
+                }
+            })
+            .collect();
+        let mut minute = IndicatorCalculator::new("btcusdt", 14, 64)
+            .with_volatility_mode(VolatilityMode::Annualized);
+        let per_minute = feed(&mut minute, &closes).volatility;
+        let expected = 0.01 * (SECONDS_PER_YEAR / 60.0).sqrt() * 100.0;
+        assert!(
+            (per_minute - expected).abs() / expected < 1e-6,
+            "{per_minute}"
+        );
+
+        // The same moves on 5-minute bars are a fifth of the variance rate.
+        let mut five = IndicatorCalculator::new("btcusdt", 14, 64)
+            .with_bar_duration(Duration::minutes(5))
+            .with_volatility_mode(VolatilityMode::Annualized);
+        let closes_five: Vec<PriceTick> = closes
+            .iter()
+            .enumerate()
+            .map(|(idx, &price)| PriceTick {
+                symbol: "btcusdt".to_string(),
+                price,
+                volume: 1.0,
+                timestamp: Utc.timestamp_opt(1_700_000_100, 0).unwrap()
+                    + Duration::minutes(5 * idx as i64),
+                side: None,
+            })
+            .collect();
+        let per_five = five.warm_up(&closes_five).unwrap().volatility;
+        assert!((per_five * 5f64.sqrt() - per_minute).abs() / per_minute < 1e-6);
+    }
+
+    #[test]
+    fn ema_crossover_and_macd_follow_a_reversal() {
+        let mut calc =
+            IndicatorCalculator::new("btcusdt", 14, 64).with_periods(&IndicatorPeriods {
+                ema: (3, 6),
+                macd: (3, 6, 3),
+                ..IndicatorPeriods::default()
+            });
+        let falling: Vec<f64> = (0..20).map(|idx| 120.0 - idx as f64).collect();
+        let metrics = feed(&mut calc, &falling);
+        let ema = metrics.ema.expect("ema");
+        assert!(ema.fast < ema.slow);
+        assert!(metrics.macd.expect("macd").line < 0.0);
+
+        let mut crosses = Vec::new();
+        for idx in 0..10 {
+            let price = 101.0 + 2.0 * idx as f64;
+            let tick = PriceTick {
+                symbol: "btcusdt".to_string(),
+                price,
+                volume: 1.0,
+                timestamp: Utc.timestamp_opt(1_700_000_040, 0).unwrap()
+                    + Duration::seconds(DEFAULT_BAR_SECS * (20 + idx)),
+                side: None,
+            };
+            if let Some(cross) = calc.on_tick(&tick).ema.and_then(|ema| ema.cross) {
+                crosses.push(cross);
             }
         }
+        assert_eq!(crosses, [CrossDirection::Bullish]);
+    }
 
-        losses = losses.max(1e-9);
-        let rs = gains / losses;
-        100.0 - (100.0 / (1.0 + rs))
+    #[test]
+    fn bollinger_flags_a_squeeze_after_volatility_contracts() {
+        let mut calc =
+            IndicatorCalculator::new("btcusdt", 14, 256).with_periods(&IndicatorPeriods {
+                bollinger: (10, 2.0),
+                ..IndicatorPeriods::default()
+            });
+        let mut closes: Vec<f64> = (0..60)
+            .map(|idx| if idx % 2 == 0 { 95.0 } else { 105.0 })
+            .collect();
+        let wide = feed(&mut calc, &closes).bollinger.expect("bands");
+        assert!(!wide.squeeze);
+        assert!((wide.middle - 100.0).abs() < 1.0);
+
+        closes.extend((0..12).map(|idx| if idx % 2 == 0 { 99.9 } else { 100.1 }));
+        let mut calc =
+            IndicatorCalculator::new("btcusdt", 14, 256).with_periods(&IndicatorPeriods {
+                bollinger: (10, 2.0),
+                ..IndicatorPeriods::default()
+            });
+        let tight = feed(&mut calc, &closes).bollinger.expect("bands");
+        assert!(tight.squeeze);
+        assert!(tight.bandwidth < wide.bandwidth);
+        assert!((0.0..=1.0).contains(&tight.percent_b));
     }
 
-    fn volatility(&self) -> f64 {
-        if self.returns.is_empty() {
-            return 0.0;
+    #[test]
+    fn atr_and_vwap_track_bar_ranges_and_sessions() {
+        let mut calc =
+            IndicatorCalculator::new("btcusdt", 14, 64).with_periods(&IndicatorPeriods {
+                atr: 5,
+                ..IndicatorPeriods::default()
+            });
+        let closes: Vec<f64> = (0..12)
+            .map(|idx| if idx % 2 == 0 { 100.0 } else { 101.0 })
+            .collect();
+        let metrics = feed(&mut calc, &closes);
+        assert!((metrics.atr.expect("atr") - 1.0).abs() < 0.2);
+        // Eleven closed bars: six at 100 and five at 101.
+        let vwap = metrics.vwap.expect("vwap");
+        assert!((vwap.vwap - (100.0 + 5.0 / 11.0)).abs() < 1e-9);
+
+        // The next UTC day starts a fresh VWAP.
+        let next_day = PriceTick {
+            symbol: "btcusdt".to_string(),
+            price: 200.0,
+            volume: 1.0,
+            timestamp: Utc.timestamp_opt(1_700_000_040 + 86_400, 0).unwrap(),
+            side: None,
+        };
+        calc.on_tick(&next_day);
+        let mut later = next_day.clone();
+        later.timestamp += Duration::seconds(DEFAULT_BAR_SECS);
+        let vwap = calc.on_tick(&later).vwap.expect("vwap");
+        assert_eq!(vwap.vwap, 200.0);
+        assert_eq!(vwap.deviation_pct, 0.0);
+    }
+
+    /// Counts ticks and closed bars.
+    struct Counter {
+        ticks: usize,
+        bars: usize,
+    }
+
+    impl Indicator for Counter {
+        fn on_tick(&mut self, _tick: &PriceTick) {
+            self.ticks += 1;
+        }
+
+        fn on_bar(&mut self, _bar: &Bar) {
+            self.bars += 1;
         }
 
-        let mean = self.returns.iter().copied().sum::<f64>() / self.returns.len() as f64;
-        let variance = self.returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>()
-            / self.returns.len() as f64;
-        (variance.sqrt() * 100.0).clamp(0.0, 10.0)
+        fn values(&self, out: &mut IndicatorValues<'_>) {
+            out.set(self.ticks as f64);
+            out.set_field("bars", self.bars as f64);
+        }
     }
 
-    fn tempo_bias(&self) -> f64 {
-        (self.volume_ratio() - 1.0).clamp(-0.5, 0.5)
+    #[test]
+    fn registry_kinds_match_the_built_in_metrics() {
+        let registry = IndicatorRegistry::builtin();
+        let mut calc = IndicatorCalculator::new("btcusdt", 14, 64);
+        for kind in ["macd", "atr"] {
+            let indicator = registry.build(&IndicatorSpec::new(kind)).unwrap();
+            calc = calc.with_indicator(kind, indicator);
+        }
+        let closes: Vec<f64> = (0..60).map(|idx| 100.0 + (idx % 7) as f64).collect();
+        let metrics = feed(&mut calc, &closes);
+        let macd = metrics.macd.clone().expect("macd");
+        assert_eq!(metrics.value("macd"), Some(macd.line));
+        assert_eq!(metrics.value("macd.signal"), Some(macd.signal));
+        assert_eq!(metrics.value("atr"), metrics.atr);
     }
-}
 
-impl Default for IndicatorCalculator {
-    fn default() -> Self {
-        Self::new("UNKNOWN", 14, 256)
+    #[test]
+    fn pluggable_indicators_publish_named_values() {
+        let mut calc = IndicatorCalculator::new("btcusdt", 14, 64)
+            .with_indicator("counter", Box::new(Counter { ticks: 0, bars: 0 }));
+        let metrics = feed(&mut calc, &[100.0, 101.0, 102.0, 103.0]);
+        assert_eq!(metrics.value("counter"), Some(4.0));
+        assert_eq!(metrics.value("counter.bars"), Some(3.0));
+        assert_eq!(metrics.values.len(), 2);
+    }
+
+    #[test]
+    fn per_bar_volatility_keeps_the_legacy_scale() {
+        let mut calc = IndicatorCalculator::new("btcusdt", 14, 64);
+        let metrics = feed(&mut calc, &[100.0, 101.0, 100.0, 101.0, 100.0, 101.0]);
+        assert!(metrics.volatility > 0.0 && metrics.volatility <= 10.0);
     }
 }
@@ -0,0 +1,276 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::{sync::mpsc::Sender, time::timeout};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{error, info};
+
+use crate::{
+    data::{
+        backoff::{Backoff, ReconnectPolicy},
+        crypto::{CONNECTION_TIMEOUT, HEARTBEAT_TIMEOUT},
+        health::FeedHealthStore,
+        source::MarketSource,
+    },
+    models::{AssetDescriptor, FeedState, PriceTick, TradeSide},
+};
+
+/// Kraken trades from the v2 public `trade` channel, one connection per asset.
+///
+/// Kraken sends a `heartbeat` frame every second once subscribed, which keeps
+/// the heartbeat timeout honest on quiet pairs.
+///
+/// Source options:
+/// - `pair`: Kraken v2 symbol (e.g. `BTC/USD`). Defaults to the asset symbol
+///   split before a known quote currency, so `btcusd` subscribes to `BTC/USD`.
+#[derive(Debug, Clone)]
+pub struct KrakenClient {
+    endpoint: String,
+    health: FeedHealthStore,
+    reconnect: ReconnectPolicy,
+}
+
+impl KrakenClient {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            health: FeedHealthStore::new(),
+            reconnect: ReconnectPolicy::default(),
+        }
+    }
+
+    /// Reports connection state, reconnects and errors to `health`.
+    pub fn with_health(mut self, health: FeedHealthStore) -> Self {
+        self.health = health;
+        self
+    }
+
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    /// Streams trades for one connection. Returns `Ok(())` once the receiver is gone.
+    async fn stream_trades(
+        &self,
+        pair: &str,
+        symbol: &str,
+        tx: &Sender<PriceTick>,
+        backoff: &mut Backoff,
+    ) -> anyhow::Result<()> {
+        info!(%pair, endpoint = %self.endpoint, "Connecting to Kraken");
+        let (ws_stream, _) = timeout(CONNECTION_TIMEOUT, connect_async(&self.endpoint)).await??;
+        let (mut sink, mut stream) = ws_stream.split();
+
+        let subscribe = json!({
+            "method": "subscribe",
+            "params": {
+                "channel": "trade",
+                "symbol": [pair],
+                "snapshot": false,
+            },
+        });
+        sink.send(Message::Text(subscribe.to_string())).await?;
+        self.health
+            .set_state(self.name(), symbol, FeedState::Connected);
+        backoff.connected();
+
+        loop {
+            tokio::select! {
+                _ = tx.closed() => return Ok(()),
+                message = timeout(HEARTBEAT_TIMEOUT, stream.next()) => {
+                    let Some(message) = message? else {
+                        anyhow::bail!("Kraken closed the connection");
+                    };
+                    let message = message?;
+                    if !message.is_text() {
+                        continue;
+                    }
+                    for tick in parse_message(message.to_text()?, symbol)? {
+                        backoff.delivered();
+                        if tx.send(tick).await.is_err() {
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl MarketSource for KrakenClient {
+    fn name(&self) -> &str {
+        "kraken"
+    }
+
+    async fn run(&self, asset: AssetDescriptor, tx: Sender<PriceTick>) -> anyhow::Result<()> {
+        let pair = match asset.source.option("pair") {
+            Some(pair) => pair.to_string(),
+            None => default_pair(&asset.symbol).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Cannot derive a Kraken pair from {}; set the `pair` option (e.g. BTC/USD)",
+                    asset.symbol
+                )
+            })?,
+        };
+
+        let mut backoff = self.reconnect.backoff();
+        loop {
+            match self
+                .stream_trades(&pair, &asset.symbol, &tx, &mut backoff)
+                .await
+            {
+                Ok(()) => return Ok(()),
+                Err(err) => {
+                    error!(%pair, %err, "Kraken stream failed");
+                    self.health.record_error(self.name(), &asset.symbol, &err);
+                }
+            }
+            if tx.is_closed() {
+                return Ok(());
+            }
+            backoff
+                .wait(&format!("Kraken {pair}"), |backoff| {
+                    self.health
+                        .record_backoff(self.name(), &asset.symbol, backoff)
+                })
+                .await;
+            self.health.record_reconnect(self.name(), &asset.symbol);
+        }
+    }
+}
+
+/// Quote currencies recognized when deriving a pair, longest first so `USDT`
+/// wins over `USD`.
+const QUOTE_CURRENCIES: [&str; 10] = [
+    "USDT", "USDC", "USD", "EUR", "GBP", "JPY", "CAD", "CHF", "AUD", "BTC",
+];
+
+/// Kraken v2 pair for an asset symbol: `BTC/USD` stays as it is and `btcusd`
+/// becomes `BTC/USD`. `None` if no known quote currency ends the symbol.
+fn default_pair(symbol: &str) -> Option<String> {
+    let symbol = symbol.to_uppercase();
+    if symbol.contains('/') {
+        return Some(symbol);
+    }
+    QUOTE_CURRENCIES.iter().find_map(|quote| {
+        let base = symbol.strip_suffix(quote)?;
+        (!base.is_empty()).then(|| format!("{base}/{quote}"))
+    })
+}
+
+/// Turns one feed message into ticks for `symbol`; a single `trade` frame may
+/// carry several trades. Control frames yield nothing and rejected requests
+/// fail the connection.
+pub fn parse_message(raw: &str, symbol: &str) -> anyhow::Result<Vec<PriceTick>> {
+    let message: FeedMessage = serde_json::from_str(raw)?;
+    if message.success == Some(false) {
+        anyhow::bail!(
+            "Kraken rejected {}: {}",
+            message.method.as_deref().unwrap_or("request"),
+            message.error.as_deref().unwrap_or("unknown error")
+        );
+    }
+    if message.channel.as_deref() != Some("trade") {
+        return Ok(Vec::new());
+    }
+
+    let trades: Vec<KrakenTrade> = serde_json::from_value(message.data)?;
+    Ok(trades.iter().map(|trade| trade.to_tick(symbol)).collect())
+}
+
+/// Channel data (`channel` + `data`) and method responses (`method` + `success`)
+/// share one loose shape; only trade data is decoded further.
+#[derive(Debug, Deserialize)]
+struct FeedMessage {
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    success: Option<bool>,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct KrakenTrade {
+    price: f64,
+    qty: f64,
+    /// Taker side.
+    side: TradeSide,
+    timestamp: DateTime<Utc>,
+}
+
+impl KrakenTrade {
+    fn to_tick(&self, symbol: &str) -> PriceTick {
+        PriceTick {
+            symbol: symbol.to_string(),
+            price: self.price,
+            volume: self.qty,
+            timestamp: self.timestamp,
+            side: Some(self.side),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRADE_UPDATE: &str = include_str!("../../tests/fixtures/kraken/trade_update.json");
+    const HEARTBEAT: &str = include_str!("../../tests/fixtures/kraken/heartbeat.json");
+    const STATUS: &str = include_str!("../../tests/fixtures/kraken/status.json");
+    const SUBSCRIBE_ACK: &str = include_str!("../../tests/fixtures/kraken/subscribe_ack.json");
+    const SUBSCRIBE_ERROR: &str = include_str!("../../tests/fixtures/kraken/subscribe_error.json");
+
+    #[test]
+    fn trade_update_yields_every_trade() {
+        let ticks = parse_message(TRADE_UPDATE, "btc/usd").unwrap();
+        assert_eq!(ticks.len(), 2);
+
+        assert_eq!(ticks[0].symbol, "btc/usd");
+        assert_eq!(ticks[0].price, 64_015.1);
+        assert_eq!(ticks[0].volume, 0.00215);
+        assert_eq!(ticks[0].side, Some(TradeSide::Buy));
+        assert_eq!(
+            ticks[0].timestamp.to_rfc3339(),
+            "2024-05-01T12:00:01.512345+00:00"
+        );
+
+        assert_eq!(ticks[1].price, 64_014.9);
+        assert_eq!(ticks[1].side, Some(TradeSide::Sell));
+        assert!(ticks[1].timestamp > ticks[0].timestamp);
+    }
+
+    #[test]
+    fn control_messages_yield_no_ticks() {
+        for raw in [HEARTBEAT, STATUS, SUBSCRIBE_ACK] {
+            assert!(parse_message(raw, "btc/usd").unwrap().is_empty(), "{raw}");
+        }
+    }
+
+    #[test]
+    fn default_pair_splits_off_the_quote_currency() {
+        assert_eq!(default_pair("btcusd").as_deref(), Some("BTC/USD"));
+        assert_eq!(default_pair("ETHUSDT").as_deref(), Some("ETH/USDT"));
+        assert_eq!(default_pair("eth/eur").as_deref(), Some("ETH/EUR"));
+        assert_eq!(default_pair("SOLBTC").as_deref(), Some("SOL/BTC"));
+        assert_eq!(default_pair("USD"), None);
+        assert_eq!(default_pair("BTCXYZ"), None);
+    }
+
+    #[test]
+    fn rejected_subscription_fails_the_connection() {
+        let err = parse_message(SUBSCRIBE_ERROR, "btc/usd").unwrap_err();
+        assert!(
+            err.to_string().contains("Currency pair not supported"),
+            "{err}"
+        );
+    }
+}
@@ -1,4 +1,21 @@
+pub mod anchor;
+pub mod backoff;
+pub mod bars;
+pub mod calendar;
+pub mod coinbase;
 pub mod crypto;
+pub mod events;
+pub mod filter;
+pub mod health;
 pub mod indicators;
+pub mod kraken;
+pub mod normalize;
+pub mod orderbook;
+pub mod recorder;
+pub mod regime;
+pub mod replay;
+pub mod signals;
+pub mod simulator;
+pub mod source;
 pub mod stocks;
 pub mod streams;
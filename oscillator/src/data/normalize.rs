@@ -0,0 +1,232 @@
+use std::collections::VecDeque;
+
+use crate::models::{NormalizedMetrics, NormalizedValue, SourceSpec};
+
+/// Closed bars each metric's distribution spans unless the asset sets
+/// `normalize_window`.
+pub const DEFAULT_NORMALIZE_WINDOW: usize = 500;
+/// Samples needed before normalized values are reported.
+const MIN_SAMPLES: usize = 30;
+
+/// Rolling distribution of one metric: running sums for the z-score and a
+/// sorted copy of the window for the percentile rank.
+#[derive(Debug, Clone)]
+struct Distribution {
+    values: VecDeque<f64>,
+    sorted: Vec<f64>,
+    capacity: usize,
+    sum: f64,
+    sum_sq: f64,
+    pushes: usize,
+}
+
+impl Distribution {
+    fn new(capacity: usize) -> Self {
+        Self {
+            values: VecDeque::with_capacity(capacity),
+            sorted: Vec::with_capacity(capacity),
+            capacity,
+            sum: 0.0,
+            sum_sq: 0.0,
+            pushes: 0,
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        if self.values.len() == self.capacity
+            && let Some(old) = self.values.pop_front()
+        {
+            self.sum -= old;
+            self.sum_sq -= old * old;
+            let idx = self.sorted.partition_point(|v| *v < old);
+            self.sorted.remove(idx);
+        }
+        self.values.push_back(value);
+        self.sum += value;
+        self.sum_sq += value * value;
+        let idx = self.sorted.partition_point(|v| *v < value);
+        self.sorted.insert(idx, value);
+
+        // Rebuild the sums once per window so float drift cannot accumulate.
+        self.pushes += 1;
+        if self.pushes >= self.capacity {
+            self.pushes = 0;
+            self.sum = self.values.iter().sum();
+            self.sum_sq = self.values.iter().map(|v| v * v).sum();
+        }
+    }
+
+    fn normalize(&self, value: f64) -> Option<NormalizedValue> {
+        let len = self.values.len();
+        if len < MIN_SAMPLES || !value.is_finite() {
+            return None;
+        }
+        let mean = self.sum / len as f64;
+        let std = (self.sum_sq / len as f64 - mean * mean).max(0.0).sqrt();
+        let z_score = if std > f64::EPSILON {
+            (value - mean) / std
+        } else {
+            0.0
+        };
+        // Mid-rank, so a value equal to the whole window sits at 0.5.
+        let below = self.sorted.partition_point(|v| *v < value);
+        let at_or_below = self.sorted.partition_point(|v| *v <= value);
+        let percentile = (below + at_or_below) as f64 / (2 * len) as f64;
+        Some(NormalizedValue {
+            z_score,
+            percentile,
+        })
+    }
+}
+
+/// Per-asset rolling normalization of the metrics the mapper reads, so every
+/// asset spans the full musical range whatever its price level or typical
+/// volatility.
+///
+/// Source options: `normalize` (`false` disables it, leaving the mapper on its
+/// absolute thresholds) and `normalize_window` (closed bars, default `500`).
+#[derive(Debug, Clone)]
+pub struct MetricNormalizer {
+    enabled: bool,
+    price: Distribution,
+    price_change: Distribution,
+    volatility: Distribution,
+    volume_ratio: Distribution,
+}
+
+impl MetricNormalizer {
+    pub fn new(window: usize) -> Self {
+        let window = window.max(MIN_SAMPLES);
+        Self {
+            enabled: true,
+            price: Distribution::new(window),
+            price_change: Distribution::new(window),
+            volatility: Distribution::new(window),
+            volume_ratio: Distribution::new(window),
+        }
+    }
+
+    pub fn from_spec(spec: &SourceSpec) -> Self {
+        let mut normalizer = Self::new(
+            spec.parse_option("normalize_window")
+                .unwrap_or(DEFAULT_NORMALIZE_WINDOW),
+        );
+        normalizer.enabled = spec.parse_option("normalize").unwrap_or(true);
+        normalizer
+    }
+
+    /// Adds one closed bar's readings to the distributions.
+    pub fn observe(&mut self, sample: &NormalizerSample) {
+        if !self.enabled {
+            return;
+        }
+        self.price.push(sample.price);
+        self.price_change.push(sample.price_change_percent);
+        self.volatility.push(sample.volatility);
+        self.volume_ratio.push(sample.volume_ratio);
+    }
+
+    /// Ranks the live readings against the distributions; `None` until enough
+    /// bars have been observed.
+    pub fn normalize(&self, sample: &NormalizerSample) -> Option<NormalizedMetrics> {
+        if !self.enabled {
+            return None;
+        }
+        Some(NormalizedMetrics {
+            price: self.price.normalize(sample.price)?,
+            price_change: self.price_change.normalize(sample.price_change_percent)?,
+            volatility: self.volatility.normalize(sample.volatility)?,
+            volume_ratio: self.volume_ratio.normalize(sample.volume_ratio)?,
+        })
+    }
+}
+
+impl Default for MetricNormalizer {
+    fn default() -> Self {
+        Self::new(DEFAULT_NORMALIZE_WINDOW)
+    }
+}
+
+/// The raw readings a `MetricNormalizer` tracks.
+#[derive(Debug, Clone, Copy)]
+pub struct NormalizerSample {
+    pub price: f64,
+    pub price_change_percent: f64,
+    pub volatility: f64,
+    pub volume_ratio: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(price: f64, volatility: f64) -> NormalizerSample {
+        NormalizerSample {
+            price,
+            price_change_percent: (price / 100.0 - 1.0) * 100.0,
+            volatility,
+            volume_ratio: 1.0,
+        }
+    }
+
+    #[test]
+    fn waits_for_enough_samples() {
+        let mut normalizer = MetricNormalizer::new(100);
+        for idx in 0..MIN_SAMPLES - 1 {
+            normalizer.observe(&sample(100.0 + idx as f64, 1.0));
+        }
+        assert!(normalizer.normalize(&sample(100.0, 1.0)).is_none());
+        normalizer.observe(&sample(100.0, 1.0));
+        assert!(normalizer.normalize(&sample(100.0, 1.0)).is_some());
+    }
+
+    #[test]
+    fn different_scales_map_to_the_same_ranks() {
+        // A 65k asset and a 500 asset moving by the same relative amounts.
+        let mut btc = MetricNormalizer::new(100);
+        let mut spy = MetricNormalizer::new(100);
+        for idx in 0..100 {
+            let wave = (idx as f64 / 7.0).sin();
+            btc.observe(&sample(65_000.0 * (1.0 + 0.02 * wave), 4.0 + wave));
+            spy.observe(&sample(500.0 * (1.0 + 0.002 * wave), 0.4 + 0.1 * wave));
+        }
+        let btc = btc.normalize(&sample(65_000.0 * 1.02, 5.0)).unwrap();
+        let spy = spy.normalize(&sample(500.0 * 1.002, 0.5)).unwrap();
+        assert!((btc.price.percentile - spy.price.percentile).abs() < 1e-9);
+        assert!((btc.volatility.z_score - spy.volatility.z_score).abs() < 1e-6);
+        assert!(btc.price.percentile > 0.95);
+    }
+
+    #[test]
+    fn percentile_and_z_score_of_a_known_window() {
+        let mut normalizer = MetricNormalizer::new(40);
+        // Evicts 0..10, leaving 10..50.
+        for idx in 0..50 {
+            normalizer.observe(&sample(idx as f64 + 1.0, idx as f64));
+        }
+        let normalized = normalizer.normalize(&sample(31.0, 29.5)).unwrap();
+        assert_eq!(normalized.volatility.percentile, 0.5);
+        assert!(normalized.volatility.z_score.abs() < 1e-9);
+        assert_eq!(
+            normalizer
+                .normalize(&sample(1.0, 100.0))
+                .unwrap()
+                .volatility
+                .percentile,
+            1.0
+        );
+    }
+
+    #[test]
+    fn disabled_normalizer_reports_nothing() {
+        let spec = SourceSpec::new("binance").with_option("normalize", "false");
+        let mut normalizer = MetricNormalizer::from_spec(&spec);
+        for idx in 0..50 {
+            normalizer.observe(&sample(100.0 + idx as f64, 1.0));
+        }
+        assert!(normalizer.normalize(&sample(120.0, 1.0)).is_none());
+    }
+}
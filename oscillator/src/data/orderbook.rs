@@ -0,0 +1,256 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use parking_lot::RwLock;
+
+use crate::models::BookMetrics;
+
+/// Prices are keyed as fixed-point integers so levels sort and compare exactly.
+const PRICE_SCALE: f64 = 1e8;
+
+/// Adjacent levels further apart than this multiple of the median level gap
+/// count as a liquidity hole.
+const HOLE_FACTOR: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct PriceKey(i64);
+
+impl PriceKey {
+    fn new(price: f64) -> Self {
+        Self((price * PRICE_SCALE).round() as i64)
+    }
+
+    fn price(self) -> f64 {
+        self.0 as f64 / PRICE_SCALE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Bid,
+    Ask,
+}
+
+/// Local L2 order book for one symbol.
+#[derive(Debug, Default, Clone)]
+pub struct OrderBook {
+    bids: BTreeMap<PriceKey, f64>,
+    asks: BTreeMap<PriceKey, f64>,
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+    }
+
+    /// Sets the resting quantity at a level; zero removes it.
+    pub fn set_level(&mut self, side: Side, price: f64, quantity: f64) {
+        let levels = match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        };
+        let key = PriceKey::new(price);
+        if quantity <= 0.0 {
+            levels.remove(&key);
+        } else {
+            levels.insert(key, quantity);
+        }
+    }
+
+    fn top_bids(&self, levels: usize) -> Vec<(f64, f64)> {
+        self.bids
+            .iter()
+            .rev()
+            .take(levels)
+            .map(|(key, qty)| (key.price(), *qty))
+            .collect()
+    }
+
+    fn top_asks(&self, levels: usize) -> Vec<(f64, f64)> {
+        self.asks
+            .iter()
+            .take(levels)
+            .map(|(key, qty)| (key.price(), *qty))
+            .collect()
+    }
+
+    /// Summarizes the top `levels` of each side, or `None` while either side is empty.
+    pub fn metrics(&self, levels: usize) -> Option<BookMetrics> {
+        let bids = self.top_bids(levels.max(1));
+        let asks = self.top_asks(levels.max(1));
+        let (best_bid, _) = *bids.first()?;
+        let (best_ask, _) = *asks.first()?;
+
+        let mid = (best_bid + best_ask) / 2.0;
+        let spread = best_ask - best_bid;
+        let spread_bps = if mid > 0.0 {
+            spread / mid * 10_000.0
+        } else {
+            0.0
+        };
+
+        let bid_volume: f64 = bids.iter().map(|(_, qty)| qty).sum();
+        let ask_volume: f64 = asks.iter().map(|(_, qty)| qty).sum();
+        let total = bid_volume + ask_volume;
+        let imbalance = if total > 0.0 {
+            (bid_volume - ask_volume) / total
+        } else {
+            0.0
+        };
+        let weighted_mid = if total > 0.0 {
+            bids.iter()
+                .chain(asks.iter())
+                .map(|(price, qty)| price * qty)
+                .sum::<f64>()
+                / total
+        } else {
+            mid
+        };
+
+        Some(BookMetrics {
+            best_bid,
+            best_ask,
+            spread,
+            spread_bps,
+            imbalance,
+            weighted_mid,
+            bid_depth: bid_volume,
+            ask_depth: ask_volume,
+            liquidity_holes: count_holes(&bids) + count_holes(&asks),
+        })
+    }
+}
+
+fn count_holes(levels: &[(f64, f64)]) -> usize {
+    if levels.len() < 3 {
+        return 0;
+    }
+    let gaps: Vec<f64> = levels
+        .windows(2)
+        .map(|pair| (pair[0].0 - pair[1].0).abs())
+        .collect();
+    let mut sorted = gaps.clone();
+    sorted.sort_by(f64::total_cmp);
+    let median = sorted[sorted.len() / 2];
+    if median <= 0.0 {
+        return 0;
+    }
+    gaps.iter()
+        .filter(|gap| **gap > median * HOLE_FACTOR)
+        .count()
+}
+
+/// Latest book summary per asset, shared between depth feeds and pipelines.
+#[derive(Debug, Clone, Default)]
+pub struct OrderBookStore {
+    inner: Arc<RwLock<HashMap<String, BookMetrics>>>,
+}
+
+impl OrderBookStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&self, symbol: &str, metrics: BookMetrics) {
+        self.inner.write().insert(symbol.to_string(), metrics);
+    }
+
+    pub fn remove(&self, symbol: &str) {
+        self.inner.write().remove(symbol);
+    }
+
+    pub fn latest(&self, symbol: &str) -> Option<BookMetrics> {
+        self.inner.read().get(symbol).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(bids: &[(f64, f64)], asks: &[(f64, f64)]) -> OrderBook {
+        let mut book = OrderBook::new();
+        for (price, qty) in bids {
+            book.set_level(Side::Bid, *price, *qty);
+        }
+        for (price, qty) in asks {
+            book.set_level(Side::Ask, *price, *qty);
+        }
+        book
+    }
+
+    #[test]
+    fn spread_and_mid_come_from_the_best_levels() {
+        let book = book(&[(99.0, 1.0), (100.0, 1.0)], &[(102.0, 1.0), (101.0, 1.0)]);
+        let metrics = book.metrics(10).unwrap();
+        assert_eq!(metrics.best_bid, 100.0);
+        assert_eq!(metrics.best_ask, 101.0);
+        assert_eq!(metrics.spread, 1.0);
+        assert!((metrics.spread_bps - 1.0 / 100.5 * 10_000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn imbalance_and_weighted_mid_lean_toward_the_heavier_side() {
+        let book = book(&[(100.0, 3.0)], &[(102.0, 1.0)]);
+        let metrics = book.metrics(10).unwrap();
+        assert_eq!(metrics.bid_depth, 3.0);
+        assert_eq!(metrics.ask_depth, 1.0);
+        assert_eq!(metrics.imbalance, 0.5);
+        // (100 * 3 + 102 * 1) / 4
+        assert_eq!(metrics.weighted_mid, 100.5);
+    }
+
+    #[test]
+    fn metrics_only_read_the_requested_levels() {
+        let book = book(&[(100.0, 1.0), (99.0, 5.0)], &[(101.0, 1.0), (102.0, 1.0)]);
+        let metrics = book.metrics(1).unwrap();
+        assert_eq!(metrics.bid_depth, 1.0);
+        assert_eq!(metrics.imbalance, 0.0);
+        assert_eq!(book.metrics(10).unwrap().bid_depth, 6.0);
+    }
+
+    #[test]
+    fn zero_quantity_removes_a_level() {
+        let mut book = book(&[(100.0, 1.0), (99.0, 1.0)], &[(101.0, 1.0)]);
+        book.set_level(Side::Bid, 100.0, 0.0);
+        assert_eq!(book.metrics(10).unwrap().best_bid, 99.0);
+        book.set_level(Side::Ask, 101.0, 0.0);
+        assert!(book.metrics(10).is_none());
+    }
+
+    #[test]
+    fn holes_are_gaps_beyond_the_hole_factor() {
+        // Median gap 1.0: a gap of exactly HOLE_FACTOR is not a hole, one past it is.
+        let levels = [
+            (100.0, 1.0),
+            (99.0, 1.0),
+            (98.0, 1.0),
+            (95.0, 1.0),
+            (94.0, 1.0),
+        ];
+        assert_eq!(count_holes(&levels), 0);
+        let levels = [
+            (100.0, 1.0),
+            (99.0, 1.0),
+            (98.0, 1.0),
+            (94.5, 1.0),
+            (93.5, 1.0),
+        ];
+        assert_eq!(count_holes(&levels), 1);
+        // Too few levels to judge.
+        assert_eq!(count_holes(&[(100.0, 1.0), (90.0, 1.0)]), 0);
+
+        let book = book(
+            &[(100.0, 1.0), (99.0, 1.0), (98.0, 1.0), (90.0, 1.0)],
+            &[(101.0, 1.0), (102.0, 1.0), (103.0, 1.0), (110.0, 1.0)],
+        );
+        assert_eq!(book.metrics(10).unwrap().liquidity_holes, 2);
+    }
+}
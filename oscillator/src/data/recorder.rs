@@ -0,0 +1,352 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    str::FromStr,
+    sync::mpsc::{Receiver, RecvTimeoutError, SyncSender, TrySendError, sync_channel},
+    time::{Duration, Instant},
+};
+
+use chrono::NaiveDate;
+use flate2::{Compression, write::GzEncoder};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::models::{PriceTick, TradeSide};
+
+/// Size of one record in the binary format: epoch millis (`i64`), price and volume (`f64`),
+/// all little-endian, followed by one trade side byte (see `side_to_byte`).
+pub const BINARY_RECORD_LEN: usize = 25;
+
+/// Header row of CSV recordings, in the column order the replay source reads.
+const CSV_HEADER: &str = "timestamp,symbol,price,volume,side\n";
+
+/// Buffered records are written out at least this often, so a crash loses at
+/// most this much and a compressed file stays readable up to the last flush.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Ticks queued for the writer thread before new ones are dropped.
+const QUEUE_CAPACITY: usize = 8192;
+
+/// Minimum time between warnings about ticks dropped on a full queue.
+const DROP_WARN_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    Jsonl,
+    Csv,
+    Binary,
+}
+
+impl RecordFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Jsonl => "jsonl",
+            Self::Csv => "csv",
+            Self::Binary => "bin",
+        }
+    }
+}
+
+impl FromStr for RecordFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "jsonl" | "json" => Ok(Self::Jsonl),
+            "csv" => Ok(Self::Csv),
+            "bin" | "binary" => Ok(Self::Binary),
+            other => Err(anyhow::anyhow!("unknown record format {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecorderConfig {
+    pub dir: PathBuf,
+    pub format: RecordFormat,
+    pub max_bytes: Option<u64>,
+    pub max_age: Option<Duration>,
+    pub compress: bool,
+}
+
+/// Appends every tick of one asset to `<dir>/<symbol>/<symbol>-<date>-<seq>.<ext>[.gz]`.
+///
+/// A new file is started when the tick's UTC day changes, or when the current file
+/// exceeds `max_bytes` (uncompressed) or `max_age`. Writes are blocking; the
+/// pipeline drives it through `RecorderHandle`, which keeps it off the runtime.
+pub struct TickRecorder {
+    config: RecorderConfig,
+    symbol: String,
+    current: Option<RecordFile>,
+    last_flush: Instant,
+}
+
+struct RecordFile {
+    writer: RecordWriter,
+    day: NaiveDate,
+    seq: u32,
+    bytes: u64,
+    opened_at: Instant,
+}
+
+enum RecordWriter {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl RecordWriter {
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Self::Plain(writer) => writer,
+            Self::Gzip(writer) => writer,
+        }
+    }
+
+    /// Flushes everything and, for gzip, writes the member trailer.
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Plain(mut writer) => writer.flush(),
+            Self::Gzip(writer) => writer.finish()?.flush(),
+        }
+    }
+}
+
+impl TickRecorder {
+    pub fn new(config: RecorderConfig, symbol: impl Into<String>) -> Self {
+        Self {
+            config,
+            symbol: symbol.into(),
+            current: None,
+            last_flush: Instant::now(),
+        }
+    }
+
+    pub fn record(&mut self, tick: &PriceTick) -> io::Result<()> {
+        let record = self.encode(tick)?;
+        let day = tick.timestamp.date_naive();
+
+        let rotate_seq = match &self.current {
+            None => Some(0),
+            Some(file) if file.day != day => Some(0),
+            Some(file) if self.is_full(file) => Some(file.seq + 1),
+            Some(_) => None,
+        };
+        if let Some(seq) = rotate_seq {
+            self.open(day, seq)?;
+        }
+
+        let file = self.current.as_mut().expect("record file open");
+        file.writer.writer().write_all(&record)?;
+        file.bytes += record.len() as u64;
+        self.flush_if_due()
+    }
+
+    /// Pushes buffered records to disk. A gzip file gets a sync flush, so it
+    /// decompresses up to this point even if the process dies before `finish`.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.last_flush = Instant::now();
+        match self.current.as_mut() {
+            Some(file) => file.writer.writer().flush(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn flush_if_due(&mut self) -> io::Result<()> {
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Completes the current file.
+    pub fn finish(&mut self) -> io::Result<()> {
+        match self.current.take() {
+            Some(file) => file.writer.finish(),
+            None => Ok(()),
+        }
+    }
+
+    fn is_full(&self, file: &RecordFile) -> bool {
+        self.config.max_bytes.is_some_and(|max| file.bytes >= max)
+            || self
+                .config
+                .max_age
+                .is_some_and(|max| file.opened_at.elapsed() >= max)
+    }
+
+    fn encode(&self, tick: &PriceTick) -> io::Result<Vec<u8>> {
+        match self.config.format {
+            RecordFormat::Jsonl => {
+                let mut line = serde_json::to_vec(tick)?;
+                line.push(b'\n');
+                Ok(line)
+            }
+            RecordFormat::Csv => {
+                let side = match tick.side {
+                    Some(TradeSide::Buy) => "buy",
+                    Some(TradeSide::Sell) => "sell",
+                    None => "",
+                };
+                let line = format!(
+                    "{},{},{},{},{side}\n",
+                    tick.timestamp.to_rfc3339(),
+                    tick.symbol,
+                    tick.price,
+                    tick.volume
+                );
+                Ok(line.into_bytes())
+            }
+            RecordFormat::Binary => {
+                let mut record = Vec::with_capacity(BINARY_RECORD_LEN);
+                record.extend_from_slice(&tick.timestamp.timestamp_millis().to_le_bytes());
+                record.extend_from_slice(&tick.price.to_le_bytes());
+                record.extend_from_slice(&tick.volume.to_le_bytes());
+                record.push(side_to_byte(tick.side));
+                Ok(record)
+            }
+        }
+    }
+
+    fn open(&mut self, day: NaiveDate, mut seq: u32) -> io::Result<()> {
+        self.finish()?;
+
+        let dir = self.config.dir.join(&self.symbol);
+        fs::create_dir_all(&dir)?;
+
+        let path = loop {
+            let mut name = format!(
+                "{}-{}-{:03}.{}",
+                self.symbol,
+                day.format("%Y-%m-%d"),
+                seq,
+                self.config.format.extension()
+            );
+            if self.config.compress {
+                name.push_str(".gz");
+            }
+            let path = dir.join(name);
+            // Never append to a file from an earlier run; gzip members and
+            // rotation bookkeeping both assume a fresh file.
+            if !path.exists() {
+                break path;
+            }
+            seq += 1;
+        };
+
+        info!(symbol = %self.symbol, path = %path.display(), "Recording ticks");
+        let file = BufWriter::new(File::create(&path)?);
+        let mut writer = if self.config.compress {
+            RecordWriter::Gzip(GzEncoder::new(file, Compression::default()))
+        } else {
+            RecordWriter::Plain(file)
+        };
+        if self.config.format == RecordFormat::Csv {
+            writer.writer().write_all(CSV_HEADER.as_bytes())?;
+        }
+
+        self.current = Some(RecordFile {
+            writer,
+            day,
+            seq,
+            bytes: 0,
+            opened_at: Instant::now(),
+        });
+        Ok(())
+    }
+}
+
+/// Feeds a `TickRecorder` running on a blocking thread, so file writes, gzip
+/// and rotation never stall the async pipeline. The queue is bounded: if the
+/// disk falls behind, ticks are dropped from the recording (never from the
+/// pipeline) and counted.
+pub struct RecorderHandle {
+    symbol: String,
+    tx: SyncSender<PriceTick>,
+    task: JoinHandle<()>,
+    dropped: u64,
+    warned_at: Option<Instant>,
+}
+
+impl RecorderHandle {
+    pub fn spawn(config: RecorderConfig, symbol: impl Into<String>) -> Self {
+        let symbol = symbol.into();
+        let (tx, rx) = sync_channel(QUEUE_CAPACITY);
+        let recorder = TickRecorder::new(config, symbol.clone());
+        let task = tokio::task::spawn_blocking(move || write_loop(recorder, rx));
+        Self {
+            symbol,
+            tx,
+            task,
+            dropped: 0,
+            warned_at: None,
+        }
+    }
+
+    /// Queues `tick` for writing. Fails once the writer has stopped.
+    pub fn record(&mut self, tick: &PriceTick) -> anyhow::Result<()> {
+        match self.tx.try_send(tick.clone()) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                self.dropped += 1;
+                if self
+                    .warned_at
+                    .is_none_or(|at| at.elapsed() >= DROP_WARN_INTERVAL)
+                {
+                    self.warned_at = Some(Instant::now());
+                    warn!(
+                        symbol = %self.symbol,
+                        dropped = self.dropped,
+                        "Tick recorder is behind - dropping ticks from the recording"
+                    );
+                }
+                Ok(())
+            }
+            Err(TrySendError::Disconnected(_)) => Err(anyhow::anyhow!("tick recorder stopped")),
+        }
+    }
+
+    /// Ticks left out of the recording because the writer fell behind.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Writes out everything queued and completes the current file.
+    pub async fn close(self) {
+        drop(self.tx);
+        let _ = self.task.await;
+    }
+}
+
+fn write_loop(mut recorder: TickRecorder, rx: Receiver<PriceTick>) {
+    loop {
+        let result = match rx.recv_timeout(FLUSH_INTERVAL) {
+            Ok(tick) => recorder.record(&tick),
+            Err(RecvTimeoutError::Timeout) => recorder.flush_if_due(),
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        if let Err(err) = result {
+            error!(symbol = %recorder.symbol, %err, "Tick recorder failed - stopping");
+            return;
+        }
+    }
+    if let Err(err) = recorder.finish() {
+        error!(symbol = %recorder.symbol, %err, "Failed to finish tick recording");
+    }
+}
+
+pub fn side_to_byte(side: Option<TradeSide>) -> u8 {
+    match side {
+        None => 0,
+        Some(TradeSide::Buy) => 1,
+        Some(TradeSide::Sell) => 2,
+    }
+}
+
+pub fn side_from_byte(byte: u8) -> Option<TradeSide> {
+    match byte {
+        1 => Some(TradeSide::Buy),
+        2 => Some(TradeSide::Sell),
+        _ => None,
+    }
+}
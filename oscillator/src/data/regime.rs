@@ -0,0 +1,381 @@
+use std::collections::VecDeque;
+
+use crate::models::{Bar, MarketRegime, SourceSpec};
+
+/// Thresholds for the regime classifier.
+///
+/// Source options (all optional):
+/// - `regime_period`: directional movement (ADX) period in bars, default `14`
+/// - `regime_confirm`: consecutive bars a new label must hold before it is
+///   reported, default `3`
+/// - `regime_trend`: ADX needed to enter a trend, default `25`; a trend is
+///   only left once ADX drops `5` below it
+#[derive(Debug, Clone)]
+pub struct RegimeConfig {
+    pub period: usize,
+    pub confirm_bars: usize,
+    pub trend_enter: f64,
+    pub trend_exit: f64,
+    /// Volatility percentile and volume ratio that mark a breakout.
+    pub breakout_percentile: f64,
+    pub breakout_volume: f64,
+    /// Stricter levels for a falling breakout to count as capitulation.
+    pub capitulation_percentile: f64,
+    pub capitulation_volume: f64,
+    /// Closed bars of volatility history the percentile is ranked against.
+    pub lookback: usize,
+}
+
+impl Default for RegimeConfig {
+    fn default() -> Self {
+        Self {
+            period: 14,
+            confirm_bars: 3,
+            trend_enter: 25.0,
+            trend_exit: 20.0,
+            breakout_percentile: 0.8,
+            breakout_volume: 1.5,
+            capitulation_percentile: 0.9,
+            capitulation_volume: 2.0,
+            lookback: 240,
+        }
+    }
+}
+
+impl RegimeConfig {
+    pub fn from_spec(spec: &SourceSpec) -> Self {
+        let defaults = Self::default();
+        let trend_enter = spec
+            .parse_option("regime_trend")
+            .unwrap_or(defaults.trend_enter);
+        Self {
+            period: spec
+                .parse_option::<usize>("regime_period")
+                .unwrap_or(defaults.period)
+                .max(2),
+            confirm_bars: spec
+                .parse_option::<usize>("regime_confirm")
+                .unwrap_or(defaults.confirm_bars)
+                .max(1),
+            trend_enter,
+            trend_exit: trend_enter - (defaults.trend_enter - defaults.trend_exit),
+            ..defaults
+        }
+    }
+}
+
+/// Wilder's directional movement index: `+DI`, `-DI` and ADX.
+#[derive(Debug, Clone)]
+struct Dmi {
+    period: usize,
+    prev: Option<(f64, f64, f64)>,
+    /// Wilder-smoothed sums of true range and directional movement.
+    tr: f64,
+    plus_dm: f64,
+    minus_dm: f64,
+    moves: usize,
+    adx: f64,
+    dx_count: usize,
+}
+
+impl Dmi {
+    fn new(period: usize) -> Self {
+        Self {
+            period,
+            prev: None,
+            tr: 0.0,
+            plus_dm: 0.0,
+            minus_dm: 0.0,
+            moves: 0,
+            adx: 0.0,
+            dx_count: 0,
+        }
+    }
+
+    fn push(&mut self, bar: &Bar) {
+        let Some((prev_high, prev_low, prev_close)) =
+            self.prev.replace((bar.high, bar.low, bar.close))
+        else {
+            return;
+        };
+        let up = bar.high - prev_high;
+        let down = prev_low - bar.low;
+        let plus = if up > down && up > 0.0 { up } else { 0.0 };
+        let minus = if down > up && down > 0.0 { down } else { 0.0 };
+        let tr = (bar.high - bar.low)
+            .max((bar.high - prev_close).abs())
+            .max((bar.low - prev_close).abs());
+
+        let period = self.period as f64;
+        self.moves += 1;
+        if self.moves <= self.period {
+            self.tr += tr;
+            self.plus_dm += plus;
+            self.minus_dm += minus;
+        } else {
+            self.tr += tr - self.tr / period;
+            self.plus_dm += plus - self.plus_dm / period;
+            self.minus_dm += minus - self.minus_dm / period;
+        }
+        if self.moves < self.period {
+            return;
+        }
+
+        let (plus_di, minus_di) = self.di();
+        let dx = if plus_di + minus_di > 0.0 {
+            (plus_di - minus_di).abs() / (plus_di + minus_di) * 100.0
+        } else {
+            0.0
+        };
+        self.dx_count += 1;
+        if self.dx_count <= self.period {
+            self.adx += (dx - self.adx) / self.dx_count as f64;
+        } else {
+            self.adx = (self.adx * (period - 1.0) + dx) / period;
+        }
+    }
+
+    fn di(&self) -> (f64, f64) {
+        if self.tr <= 0.0 {
+            return (0.0, 0.0);
+        }
+        (
+            self.plus_dm / self.tr * 100.0,
+            self.minus_dm / self.tr * 100.0,
+        )
+    }
+
+    fn is_ready(&self) -> bool {
+        self.dx_count >= self.period
+    }
+}
+
+/// Labels an asset's regime from closed bars by combining directional movement
+/// (ADX and `±DI`), where the current volatility ranks in its recent history,
+/// and the volume ratio.
+///
+/// Two layers of hysteresis keep the label from flickering: a trend is entered
+/// at `trend_enter` but only left below `trend_exit`, and any new label must
+/// win `confirm_bars` bars in a row before it replaces the current one.
+#[derive(Debug, Clone)]
+pub struct RegimeClassifier {
+    config: RegimeConfig,
+    dmi: Dmi,
+    volatility: VecDeque<f64>,
+    current: Option<MarketRegime>,
+    candidate: Option<(MarketRegime, usize)>,
+}
+
+impl RegimeClassifier {
+    pub fn new(config: RegimeConfig) -> Self {
+        Self {
+            dmi: Dmi::new(config.period),
+            volatility: VecDeque::with_capacity(config.lookback),
+            config,
+            current: None,
+            candidate: None,
+        }
+    }
+
+    pub fn config(&self) -> &RegimeConfig {
+        &self.config
+    }
+
+    /// The confirmed regime; `None` until the ADX has warmed up.
+    pub fn current(&self) -> Option<MarketRegime> {
+        self.current
+    }
+
+    /// Updates with a closed bar and the volatility and volume ratio after it.
+    pub fn push(&mut self, bar: &Bar, volatility: f64, volume_ratio: f64) -> Option<MarketRegime> {
+        self.dmi.push(bar);
+        let percentile = self.rank(volatility);
+        if self.volatility.len() == self.config.lookback {
+            self.volatility.pop_front();
+        }
+        self.volatility.push_back(volatility);
+        if !self.dmi.is_ready() {
+            return None;
+        }
+
+        let raw = self.classify(percentile, volume_ratio);
+        let Some(current) = self.current else {
+            self.current = Some(raw);
+            return self.current;
+        };
+        if raw == current {
+            self.candidate = None;
+            return self.current;
+        }
+        let count = match self.candidate {
+            Some((label, count)) if label == raw => count + 1,
+            _ => 1,
+        };
+        if count >= self.config.confirm_bars {
+            self.current = Some(raw);
+            self.candidate = None;
+        } else {
+            self.candidate = Some((raw, count));
+        }
+        self.current
+    }
+
+    fn classify(&self, percentile: f64, volume_ratio: f64) -> MarketRegime {
+        let (plus_di, minus_di) = self.dmi.di();
+        let falling = minus_di > plus_di;
+        if percentile >= self.config.capitulation_percentile
+            && volume_ratio >= self.config.capitulation_volume
+            && falling
+        {
+            return MarketRegime::Capitulation;
+        }
+        if percentile >= self.config.breakout_percentile
+            && volume_ratio >= self.config.breakout_volume
+        {
+            return MarketRegime::Breakout;
+        }
+
+        let trending = matches!(
+            self.current,
+            Some(MarketRegime::TrendingUp | MarketRegime::TrendingDown)
+        );
+        let threshold = if trending {
+            self.config.trend_exit
+        } else {
+            self.config.trend_enter
+        };
+        if self.dmi.adx < threshold {
+            MarketRegime::Ranging
+        } else if falling {
+            MarketRegime::TrendingDown
+        } else {
+            MarketRegime::TrendingUp
+        }
+    }
+
+    /// Fraction of the volatility history at or below `value`. Without enough
+    /// history nothing counts as extreme.
+    fn rank(&self, value: f64) -> f64 {
+        if self.volatility.len() < self.config.period {
+            return 0.5;
+        }
+        let below = self.volatility.iter().filter(|v| **v <= value).count();
+        below as f64 / self.volatility.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::*;
+
+    fn bar(idx: i64, close: f64) -> Bar {
+        let start = Utc.timestamp_opt(1_700_000_040, 0).unwrap() + Duration::minutes(idx);
+        Bar {
+            symbol: "btcusdt".to_string(),
+            start,
+            end: start + Duration::minutes(1),
+            open: close,
+            high: close + 0.5,
+            low: close - 0.5,
+            close,
+            volume: 1.0,
+            buy_volume: 0.0,
+            sell_volume: 0.0,
+            trades: 1,
+        }
+    }
+
+    fn classifier() -> RegimeClassifier {
+        RegimeClassifier::new(RegimeConfig {
+            period: 5,
+            ..RegimeConfig::default()
+        })
+    }
+
+    /// Feeds `closes` starting at bar `from` with flat volatility and volume.
+    fn run(regime: &mut RegimeClassifier, from: i64, closes: &[f64]) -> Vec<MarketRegime> {
+        closes
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, &close)| regime.push(&bar(from + idx as i64, close), 1.0, 1.0))
+            .collect()
+    }
+
+    #[test]
+    fn labels_trends_and_ranges() {
+        let mut regime = classifier();
+        let rising: Vec<f64> = (0..30).map(|idx| 100.0 + idx as f64).collect();
+        let labels = run(&mut regime, 0, &rising);
+        assert_eq!(labels.last(), Some(&MarketRegime::TrendingUp));
+
+        let mut regime = classifier();
+        let falling: Vec<f64> = (0..30).map(|idx| 100.0 - idx as f64).collect();
+        run(&mut regime, 0, &falling);
+        assert_eq!(regime.current(), Some(MarketRegime::TrendingDown));
+
+        let mut regime = classifier();
+        let chop: Vec<f64> = (0..60)
+            .map(|idx| 100.0 + if idx % 2 == 0 { 0.0 } else { 0.2 })
+            .collect();
+        run(&mut regime, 0, &chop);
+        assert_eq!(regime.current(), Some(MarketRegime::Ranging));
+    }
+
+    #[test]
+    fn volatility_and_volume_spikes_mark_breakouts_and_capitulation() {
+        let mut regime = RegimeClassifier::new(RegimeConfig {
+            period: 5,
+            confirm_bars: 1,
+            ..RegimeConfig::default()
+        });
+        let chop: Vec<f64> = (0..40)
+            .map(|idx| 100.0 + if idx % 2 == 0 { 0.0 } else { 0.2 })
+            .collect();
+        run(&mut regime, 0, &chop);
+        assert_eq!(
+            regime.push(&bar(40, 103.0), 5.0, 2.5),
+            Some(MarketRegime::Breakout)
+        );
+
+        let crash: Vec<f64> = (0..8).map(|idx| 100.0 - 3.0 * idx as f64).collect();
+        let labels: Vec<_> = crash
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, &close)| regime.push(&bar(41 + idx as i64, close), 9.0, 3.0))
+            .collect();
+        assert_eq!(labels.last(), Some(&MarketRegime::Capitulation));
+    }
+
+    #[test]
+    fn new_labels_need_confirmation() {
+        let mut regime = classifier();
+        let chop: Vec<f64> = (0..40)
+            .map(|idx| 100.0 + if idx % 2 == 0 { 0.0 } else { 0.2 })
+            .collect();
+        run(&mut regime, 0, &chop);
+        assert_eq!(regime.current(), Some(MarketRegime::Ranging));
+
+        // A single spike bar is not enough to leave the range.
+        assert_eq!(
+            regime.push(&bar(40, 101.0), 5.0, 2.5),
+            Some(MarketRegime::Ranging)
+        );
+        assert_eq!(
+            regime.push(&bar(41, 100.0), 1.0, 1.0),
+            Some(MarketRegime::Ranging)
+        );
+        // Three in a row are.
+        for idx in 42..44 {
+            assert_eq!(
+                regime.push(&bar(idx, 101.0), 5.0, 2.5),
+                Some(MarketRegime::Ranging)
+            );
+        }
+        assert_eq!(
+            regime.push(&bar(44, 101.5), 5.0, 2.5),
+            Some(MarketRegime::Breakout)
+        );
+    }
+}
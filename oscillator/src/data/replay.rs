@@ -0,0 +1,480 @@
+use std::{io::Read, path::Path, time::Duration};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use tokio::{sync::mpsc::Sender, time::sleep};
+use tracing::{info, warn};
+
+use crate::{
+    data::{
+        recorder::{BINARY_RECORD_LEN, side_from_byte},
+        source::MarketSource,
+    },
+    models::{AssetDescriptor, PriceTick, SourceSpec, TradeSide},
+};
+
+/// Replays recorded `PriceTick`s from a CSV, JSONL or binary recorder file.
+///
+/// A file cut short, e.g. by a crash while recording, replays up to its last
+/// complete record.
+///
+/// Source options:
+/// - `path` (required): file to read; `.csv` is parsed as CSV, `.bin` as the
+///   `TickRecorder` binary format, anything else as JSONL. A trailing `.gz` is decompressed.
+/// - `speed`: playback multiplier, `1` = real time (default), `0` or `max` = as
+///   fast as possible; negative values are rejected
+/// - `loop`: restart from the beginning when the file is exhausted
+/// - `start` / `end`: RFC 3339 window; records outside it are skipped
+/// - `symbol`: only replay records for this symbol (defaults to the asset symbol)
+#[derive(Debug, Clone, Default)]
+pub struct ReplaySource;
+
+impl ReplaySource {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ReplayOptions {
+    path: String,
+    speed: Option<f64>,
+    looped: bool,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    symbol: String,
+}
+
+impl ReplayOptions {
+    fn from_spec(spec: &SourceSpec, asset_symbol: &str) -> anyhow::Result<Self> {
+        let path = spec
+            .option("path")
+            .context("replay source requires a `path` option")?
+            .to_string();
+        let speed = match spec.option("speed") {
+            None => Some(1.0),
+            Some("max") => None,
+            Some(raw) => {
+                let speed: f64 = raw.parse().context("invalid replay `speed`")?;
+                anyhow::ensure!(speed >= 0.0, "replay `speed` must not be negative");
+                (speed > 0.0).then_some(speed)
+            }
+        };
+        let looped = spec.parse_option("loop").unwrap_or(false);
+        let start = spec.option("start").map(parse_timestamp).transpose()?;
+        let end = spec.option("end").map(parse_timestamp).transpose()?;
+        let symbol = spec.option("symbol").unwrap_or(asset_symbol).to_string();
+
+        Ok(Self {
+            path,
+            speed,
+            looped,
+            start,
+            end,
+            symbol,
+        })
+    }
+
+    fn accepts(&self, record: &ReplayRecord) -> bool {
+        if self.start.is_some_and(|start| record.timestamp < start) {
+            return false;
+        }
+        if self.end.is_some_and(|end| record.timestamp > end) {
+            return false;
+        }
+        record
+            .symbol
+            .as_ref()
+            .is_none_or(|symbol| symbol.eq_ignore_ascii_case(&self.symbol))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ReplayRecord {
+    #[serde(default)]
+    symbol: Option<String>,
+    price: f64,
+    #[serde(default)]
+    volume: f64,
+    timestamp: DateTime<Utc>,
+    #[serde(default)]
+    side: Option<TradeSide>,
+}
+
+#[async_trait]
+impl MarketSource for ReplaySource {
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    async fn run(&self, asset: AssetDescriptor, tx: Sender<PriceTick>) -> anyhow::Result<()> {
+        let options = ReplayOptions::from_spec(&asset.source, &asset.symbol)?;
+        let records = load_records(&options).await?;
+        anyhow::ensure!(!records.is_empty(), "no replay records in {}", options.path);
+
+        info!(
+            symbol = %asset.symbol,
+            path = %options.path,
+            records = records.len(),
+            "Starting replay"
+        );
+
+        let first = records[0].timestamp;
+        let last = records[records.len() - 1].timestamp;
+        // Shift each loop pass so emitted timestamps keep increasing.
+        let pass_span = (last - first) + chrono::Duration::seconds(1);
+        let mut offset = chrono::Duration::zero();
+
+        loop {
+            let mut previous: Option<DateTime<Utc>> = None;
+            for record in &records {
+                if let (Some(speed), Some(prev)) = (options.speed, previous) {
+                    let gap = (record.timestamp - prev).to_std().unwrap_or_default();
+                    if !gap.is_zero() {
+                        sleep(Duration::from_secs_f64(gap.as_secs_f64() / speed)).await;
+                    }
+                }
+                previous = Some(record.timestamp);
+
+                let tick = PriceTick {
+                    symbol: asset.symbol.clone(),
+                    price: record.price,
+                    volume: record.volume,
+                    timestamp: record.timestamp + offset,
+                    side: record.side,
+                };
+                if tx.send(tick).await.is_err() {
+                    return Ok(());
+                }
+                if options.speed.is_none() {
+                    tokio::task::yield_now().await;
+                }
+            }
+
+            if !options.looped {
+                info!(symbol = %asset.symbol, "Replay finished");
+                return Ok(());
+            }
+            offset += pass_span;
+        }
+    }
+}
+
+async fn load_records(options: &ReplayOptions) -> anyhow::Result<Vec<ReplayRecord>> {
+    let mut raw = tokio::fs::read(&options.path)
+        .await
+        .with_context(|| format!("reading replay file {}", options.path))?;
+
+    let mut path = Path::new(&options.path);
+    if has_extension(path, "gz") {
+        let mut decoded = Vec::new();
+        match GzDecoder::new(raw.as_slice()).read_to_end(&mut decoded) {
+            Ok(_) => {}
+            // A gzip member without its trailer: keep what was flushed.
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof && !decoded.is_empty() => {
+                warn!(path = %options.path, "Replay file is truncated - using its complete records");
+            }
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("decompressing replay file {}", options.path));
+            }
+        }
+        raw = decoded;
+        path = Path::new(path.file_stem().unwrap_or_default());
+    }
+
+    let mut records = if has_extension(path, "csv") {
+        parse_csv(std::str::from_utf8(&raw)?)?
+    } else if has_extension(path, "bin") {
+        parse_binary(&raw)?
+    } else {
+        parse_jsonl(std::str::from_utf8(&raw)?)?
+    };
+    records.retain(|record| options.accepts(record));
+    records.sort_by_key(|record| record.timestamp);
+    Ok(records)
+}
+
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension()
+        .is_some_and(|found| found.eq_ignore_ascii_case(ext))
+}
+
+/// Drops a final line that is both unterminated and unparsable: the partial
+/// record a writer left behind when it was cut off.
+fn drop_partial_line<T>(raw: &str, records: &mut Vec<anyhow::Result<T>>) {
+    if !raw.ends_with('\n') && records.last().is_some_and(|last| last.is_err()) {
+        warn!("Replay file ends in a partial record - skipping it");
+        records.pop();
+    }
+}
+
+fn parse_binary(raw: &[u8]) -> anyhow::Result<Vec<ReplayRecord>> {
+    if !raw.len().is_multiple_of(BINARY_RECORD_LEN) {
+        warn!("Replay file ends in a partial record - skipping it");
+    }
+    raw.chunks_exact(BINARY_RECORD_LEN)
+        .map(|record| {
+            let field = |idx: usize| {
+                let bytes: [u8; 8] = record[idx * 8..idx * 8 + 8].try_into().expect("8 bytes");
+                bytes
+            };
+            let millis = i64::from_le_bytes(field(0));
+            Ok(ReplayRecord {
+                symbol: None,
+                price: f64::from_le_bytes(field(1)),
+                volume: f64::from_le_bytes(field(2)),
+                timestamp: Utc
+                    .timestamp_millis_opt(millis)
+                    .single()
+                    .with_context(|| format!("invalid epoch millis {millis}"))?,
+                side: side_from_byte(record[24]),
+            })
+        })
+        .collect()
+}
+
+fn parse_jsonl(raw: &str) -> anyhow::Result<Vec<ReplayRecord>> {
+    let mut records: Vec<anyhow::Result<ReplayRecord>> = raw
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            serde_json::from_str(line).with_context(|| format!("replay line {}", idx + 1))
+        })
+        .collect();
+    drop_partial_line(raw, &mut records);
+    records.into_iter().collect()
+}
+
+/// Parses a headed CSV with `timestamp`, `price` and optional `volume`/`symbol`/`side` columns.
+fn parse_csv(raw: &str) -> anyhow::Result<Vec<ReplayRecord>> {
+    let mut lines = raw.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<String> = lines
+        .next()
+        .context("empty replay csv")?
+        .split(',')
+        .map(|col| col.trim().to_ascii_lowercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|col| col == name);
+    let ts_col = column("timestamp").context("replay csv missing `timestamp` column")?;
+    let price_col = column("price").context("replay csv missing `price` column")?;
+    let volume_col = column("volume");
+    let symbol_col = column("symbol");
+    let side_col = column("side");
+
+    let mut records: Vec<anyhow::Result<ReplayRecord>> = lines
+        .enumerate()
+        .map(|(idx, line)| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let field = |col: usize| {
+                fields
+                    .get(col)
+                    .copied()
+                    .with_context(|| format!("replay csv row {} is short", idx + 2))
+            };
+            Ok(ReplayRecord {
+                symbol: symbol_col
+                    .and_then(|col| fields.get(col))
+                    .map(|s| s.to_string()),
+                price: field(price_col)?.parse().context("invalid csv price")?,
+                volume: match volume_col {
+                    Some(col) => field(col)?.parse().context("invalid csv volume")?,
+                    None => 0.0,
+                },
+                timestamp: parse_timestamp(field(ts_col)?)?,
+                side: side_col
+                    .and_then(|col| fields.get(col))
+                    .and_then(|side| parse_side(side)),
+            })
+        })
+        .collect();
+    drop_partial_line(raw, &mut records);
+    records.into_iter().collect()
+}
+
+fn parse_side(raw: &str) -> Option<TradeSide> {
+    match raw.to_ascii_lowercase().as_str() {
+        "buy" | "b" => Some(TradeSide::Buy),
+        "sell" | "s" => Some(TradeSide::Sell),
+        _ => None,
+    }
+}
+
+/// Accepts RFC 3339 strings or integer epoch milliseconds.
+fn parse_timestamp(raw: &str) -> anyhow::Result<DateTime<Utc>> {
+    if let Ok(millis) = raw.parse::<i64>() {
+        return Utc
+            .timestamp_millis_opt(millis)
+            .single()
+            .with_context(|| format!("invalid epoch millis {raw}"));
+    }
+    Ok(DateTime::parse_from_rfc3339(raw)
+        .with_context(|| format!("invalid timestamp {raw}"))?
+        .with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::data::recorder::{RecordFormat, RecorderConfig, RecorderHandle, TickRecorder};
+
+    fn fixture(name: &str) -> String {
+        format!(
+            "{}/tests/fixtures/replay/{name}",
+            env!("CARGO_MANIFEST_DIR")
+        )
+    }
+
+    fn options(path: &str) -> ReplayOptions {
+        let spec = SourceSpec::new("replay").with_option("path", path);
+        ReplayOptions::from_spec(&spec, "btcusdt").unwrap()
+    }
+
+    /// `(epoch millis, price, volume, side)` of every fixture's ticks.
+    fn summary(records: &[ReplayRecord]) -> Vec<(i64, f64, f64, Option<TradeSide>)> {
+        records
+            .iter()
+            .map(|r| (r.timestamp.timestamp_millis(), r.price, r.volume, r.side))
+            .collect()
+    }
+
+    const EXPECTED: [(i64, f64, f64, Option<TradeSide>); 3] = [
+        (1_772_445_600_000, 65_000.5, 0.25, Some(TradeSide::Buy)),
+        (1_772_445_601_000, 65_001.0, 0.5, Some(TradeSide::Sell)),
+        (1_772_445_602_500, 64_999.75, 1.0, None),
+    ];
+
+    #[tokio::test]
+    async fn every_format_loads_the_same_ticks() {
+        for name in ["ticks.csv", "ticks.jsonl", "ticks.jsonl.gz", "ticks.bin"] {
+            let records = load_records(&options(&fixture(name))).await.unwrap();
+            assert_eq!(summary(&records), EXPECTED, "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn window_filters_records() {
+        let spec = SourceSpec::new("replay")
+            .with_option("path", fixture("ticks.jsonl"))
+            .with_option("start", "2026-03-02T10:00:00.500Z")
+            .with_option("end", "2026-03-02T10:00:02Z");
+        let options = ReplayOptions::from_spec(&spec, "btcusdt").unwrap();
+        let records = load_records(&options).await.unwrap();
+        assert_eq!(summary(&records), EXPECTED[1..2]);
+    }
+
+    #[test]
+    fn parses_speed() {
+        let speed = |raw: &str| {
+            let spec = SourceSpec::new("replay")
+                .with_option("path", "ticks.csv")
+                .with_option("speed", raw);
+            ReplayOptions::from_spec(&spec, "btcusdt").map(|options| options.speed)
+        };
+        assert_eq!(speed("2").unwrap(), Some(2.0));
+        assert_eq!(speed("0").unwrap(), None);
+        assert_eq!(speed("max").unwrap(), None);
+        assert!(speed("-1").is_err());
+        assert!(speed("fast").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(parse_csv("time,price\n2026-03-02T10:00:00Z,1\n").is_err());
+        assert!(parse_csv("timestamp,price\n2026-03-02T10:00:00Z\n").is_err());
+        assert!(parse_jsonl("{\"price\": 1}\n").is_err());
+        assert!(parse_jsonl("{\"price\": 1}\n{\"price\": 2, \"timestamp\": 1}\n").is_err());
+    }
+
+    fn recorder_config(format: RecordFormat, compress: bool) -> RecorderConfig {
+        RecorderConfig {
+            dir: std::env::temp_dir().join(format!("oscillator-replay-{}", uuid::Uuid::new_v4())),
+            format,
+            max_bytes: None,
+            max_age: None,
+            compress,
+        }
+    }
+
+    fn ticks() -> Vec<PriceTick> {
+        EXPECTED
+            .iter()
+            .map(|&(millis, price, volume, side)| PriceTick {
+                symbol: "btcusdt".to_string(),
+                price,
+                volume,
+                timestamp: Utc.timestamp_millis_opt(millis).unwrap(),
+                side,
+            })
+            .collect()
+    }
+
+    /// The single file recorded under `config`.
+    fn recorded(config: &RecorderConfig) -> String {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(config.dir.join("btcusdt"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        files.pop().unwrap().to_string_lossy().into_owned()
+    }
+
+    #[tokio::test]
+    async fn recordings_replay_in_every_format() {
+        for format in [RecordFormat::Csv, RecordFormat::Jsonl, RecordFormat::Binary] {
+            for compress in [false, true] {
+                let config = recorder_config(format, compress);
+                let mut recorder = RecorderHandle::spawn(config.clone(), "btcusdt");
+                for tick in ticks() {
+                    recorder.record(&tick).unwrap();
+                }
+                recorder.close().await;
+
+                let records = load_records(&options(&recorded(&config))).await.unwrap();
+                assert_eq!(summary(&records), EXPECTED, "{format:?} gz={compress}");
+                std::fs::remove_dir_all(&config.dir).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn truncated_recordings_replay_their_complete_records() {
+        for format in [RecordFormat::Csv, RecordFormat::Jsonl, RecordFormat::Binary] {
+            for compress in [false, true] {
+                let config = recorder_config(format, compress);
+                let mut recorder = TickRecorder::new(config.clone(), "btcusdt");
+                for tick in ticks() {
+                    recorder.record(&tick).unwrap();
+                }
+                recorder.flush().unwrap();
+                // Killed before the gzip trailer is written.
+                std::mem::forget(recorder);
+
+                let path = recorded(&config);
+                if !compress {
+                    // ...or halfway through writing one more record.
+                    let partial: &[u8] = match format {
+                        RecordFormat::Csv => b"2026-03-02T10:00:03Z,btcusdt,650",
+                        RecordFormat::Jsonl => b"{\"symbol\":\"btcusdt\",\"pri",
+                        RecordFormat::Binary => &[0; 10],
+                    };
+                    let mut file = std::fs::OpenOptions::new()
+                        .append(true)
+                        .open(&path)
+                        .unwrap();
+                    std::io::Write::write_all(&mut file, partial).unwrap();
+                }
+
+                let records = load_records(&options(&path)).await.unwrap();
+                assert_eq!(summary(&records), EXPECTED, "{format:?} gz={compress}");
+                std::fs::remove_dir_all(&config.dir).unwrap();
+            }
+        }
+    }
+}
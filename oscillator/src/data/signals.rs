@@ -0,0 +1,420 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, ensure};
+use chrono_tz::Tz;
+
+use crate::{
+    data::indicators::{
+        Atr, Bollinger, Ema, EmaCross, IndicatorPeriods, Macd, RollingWindow, Vwap,
+    },
+    models::{Bar, IndicatorSpec, PriceTick},
+};
+
+/// A pluggable indicator run alongside the built-in set.
+///
+/// Indicators see every accepted tick and every closed bar of their asset and
+/// publish any number of named values into `MarketMetrics::values`, so a new
+/// signal for the music needs a registered factory rather than changes to the
+/// calculator or the metrics model.
+pub trait Indicator: Send {
+    /// Called for each accepted tick, after any bar it closed was passed to `on_bar`.
+    fn on_tick(&mut self, _tick: &PriceTick) {}
+
+    fn on_bar(&mut self, bar: &Bar);
+
+    /// Writes the current outputs. Indicators that are not warmed up yet simply
+    /// write nothing.
+    fn values(&self, out: &mut IndicatorValues<'_>);
+}
+
+/// Collects one indicator's outputs under its configured name.
+pub struct IndicatorValues<'a> {
+    name: &'a str,
+    values: &'a mut BTreeMap<String, f64>,
+}
+
+impl<'a> IndicatorValues<'a> {
+    pub fn new(name: &'a str, values: &'a mut BTreeMap<String, f64>) -> Self {
+        Self { name, values }
+    }
+
+    /// Publishes the indicator's main output under its bare name.
+    pub fn set(&mut self, value: f64) {
+        if value.is_finite() {
+            self.values.insert(self.name.to_string(), value);
+        }
+    }
+
+    /// Publishes a secondary output as `name.field`.
+    pub fn set_field(&mut self, field: &str, value: f64) {
+        if value.is_finite() {
+            self.values.insert(format!("{}.{field}", self.name), value);
+        }
+    }
+}
+
+pub type IndicatorFactory =
+    Arc<dyn Fn(&IndicatorSpec) -> anyhow::Result<Box<dyn Indicator>> + Send + Sync>;
+
+/// Indicator factories by `kind`, used to build each asset's configured indicators.
+#[derive(Clone, Default)]
+pub struct IndicatorRegistry {
+    factories: HashMap<String, IndicatorFactory>,
+}
+
+impl IndicatorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with the bundled kinds: `sma`, `ema`, `roc` (all `period`),
+    /// `stochastic` (`period`, `smooth`), and the calculator's own `macd`
+    /// (`fast`, `slow`, `signal`), `bollinger` (`period`, `width`),
+    /// `ema_cross` (`fast`, `slow`), `atr` (`period`) and `vwap` (`timezone`),
+    /// for assets that want them on other settings too.
+    pub fn builtin() -> Self {
+        let defaults = IndicatorPeriods::default();
+        let mut registry = Self::new();
+        registry
+            .register("sma", |spec| {
+                Ok(Box::new(Sma::new(period(spec, "period", 20)?)) as Box<dyn Indicator>)
+            })
+            .register("ema", |spec| {
+                Ok(Box::new(Ema::new(period(spec, "period", 20)?)) as Box<dyn Indicator>)
+            })
+            .register("roc", |spec| {
+                Ok(Box::new(RateOfChange::new(period(spec, "period", 10)?)) as Box<dyn Indicator>)
+            })
+            .register("stochastic", |spec| {
+                Ok(Box::new(Stochastic::new(
+                    period(spec, "period", 14)?,
+                    period(spec, "smooth", 3)?,
+                )) as Box<dyn Indicator>)
+            })
+            .register("macd", move |spec| {
+                Ok(Box::new(Macd::new((
+                    period(spec, "fast", defaults.macd.0)?,
+                    period(spec, "slow", defaults.macd.1)?,
+                    period(spec, "signal", defaults.macd.2)?,
+                ))) as Box<dyn Indicator>)
+            })
+            .register("bollinger", move |spec| {
+                Ok(Box::new(Bollinger::new((
+                    period(spec, "period", defaults.bollinger.0)?,
+                    width(spec, defaults.bollinger.1)?,
+                ))) as Box<dyn Indicator>)
+            })
+            .register("ema_cross", move |spec| {
+                Ok(Box::new(EmaCross::new((
+                    period(spec, "fast", defaults.ema.0)?,
+                    period(spec, "slow", defaults.ema.1)?,
+                ))) as Box<dyn Indicator>)
+            })
+            .register("atr", move |spec| {
+                Ok(Box::new(Atr::new(period(spec, "period", defaults.atr)?)) as Box<dyn Indicator>)
+            })
+            .register("vwap", |spec| {
+                let timezone = match spec.params.get("timezone") {
+                    Some(raw) => raw
+                        .parse::<Tz>()
+                        .map_err(|_| anyhow!("vwap timezone {raw} is not a known timezone"))?,
+                    None => Tz::UTC,
+                };
+                Ok(Box::new(Vwap::new(timezone)) as Box<dyn Indicator>)
+            });
+        registry
+    }
+
+    pub fn register<F>(&mut self, kind: impl Into<String>, factory: F) -> &mut Self
+    where
+        F: Fn(&IndicatorSpec) -> anyhow::Result<Box<dyn Indicator>> + Send + Sync + 'static,
+    {
+        self.factories.insert(kind.into(), Arc::new(factory));
+        self
+    }
+
+    pub fn build(&self, spec: &IndicatorSpec) -> anyhow::Result<Box<dyn Indicator>> {
+        let factory = self
+            .factories
+            .get(&spec.kind)
+            .ok_or_else(|| anyhow!("unknown indicator kind {}", spec.kind))?;
+        factory(spec)
+    }
+
+    pub fn kinds(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(String::as_str)
+    }
+}
+
+fn period(spec: &IndicatorSpec, key: &str, default: usize) -> anyhow::Result<usize> {
+    let value = match spec.params.get(key) {
+        Some(raw) => raw
+            .parse()
+            .map_err(|_| anyhow!("{} {key} must be a whole number, got {raw}", spec.kind))?,
+        None => default,
+    };
+    ensure!(value > 0, "{} {key} must be positive", spec.kind);
+    Ok(value)
+}
+
+fn width(spec: &IndicatorSpec, default: f64) -> anyhow::Result<f64> {
+    let value = match spec.params.get("width") {
+        Some(raw) => raw
+            .parse()
+            .map_err(|_| anyhow!("{} width must be a number, got {raw}", spec.kind))?,
+        None => default,
+    };
+    ensure!(
+        value > 0.0 && f64::is_finite(value),
+        "{} width must be positive",
+        spec.kind
+    );
+    Ok(value)
+}
+
+/// Simple moving average of the close.
+struct Sma {
+    closes: RollingWindow,
+}
+
+impl Sma {
+    fn new(period: usize) -> Self {
+        Self {
+            closes: RollingWindow::new(period),
+        }
+    }
+}
+
+impl Indicator for Sma {
+    fn on_bar(&mut self, bar: &Bar) {
+        self.closes.push(bar.close);
+    }
+
+    fn values(&self, out: &mut IndicatorValues<'_>) {
+        if self.closes.is_full() {
+            out.set(self.closes.mean());
+        }
+    }
+}
+
+/// Percent change of the close over `period` bars.
+struct RateOfChange {
+    closes: RollingWindow,
+}
+
+impl RateOfChange {
+    fn new(period: usize) -> Self {
+        Self {
+            closes: RollingWindow::new(period + 1),
+        }
+    }
+}
+
+impl Indicator for RateOfChange {
+    fn on_bar(&mut self, bar: &Bar) {
+        self.closes.push(bar.close);
+    }
+
+    fn values(&self, out: &mut IndicatorValues<'_>) {
+        if !self.closes.is_full() {
+            return;
+        }
+        let (Some(first), Some(last)) = (self.closes.first(), self.closes.last()) else {
+            return;
+        };
+        if first > 0.0 {
+            out.set((last / first - 1.0) * 100.0);
+        }
+    }
+}
+
+/// Stochastic oscillator: where the close sits in the `period`-bar high-low
+/// range (%K, 0-100, the main output) and its `smooth`-bar average (`d`).
+struct Stochastic {
+    period: usize,
+    ranges: VecDeque<(f64, f64)>,
+    ks: RollingWindow,
+}
+
+impl Stochastic {
+    fn new(period: usize, smooth: usize) -> Self {
+        Self {
+            period,
+            ranges: VecDeque::with_capacity(period),
+            ks: RollingWindow::new(smooth),
+        }
+    }
+}
+
+impl Indicator for Stochastic {
+    fn on_bar(&mut self, bar: &Bar) {
+        if self.ranges.len() == self.period {
+            self.ranges.pop_front();
+        }
+        self.ranges.push_back((bar.high, bar.low));
+        if self.ranges.len() < self.period {
+            return;
+        }
+        let high = self.ranges.iter().map(|r| r.0).fold(f64::MIN, f64::max);
+        let low = self.ranges.iter().map(|r| r.1).fold(f64::MAX, f64::min);
+        let k = if high > low {
+            (bar.close - low) / (high - low) * 100.0
+        } else {
+            50.0
+        };
+        self.ks.push(k);
+    }
+
+    fn values(&self, out: &mut IndicatorValues<'_>) {
+        let Some(k) = self.ks.last() else {
+            return;
+        };
+        out.set(k);
+        if self.ks.is_full() {
+            out.set_field("d", self.ks.mean());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::*;
+
+    fn bar(idx: i64, high: f64, low: f64, close: f64) -> Bar {
+        let start = Utc.timestamp_opt(1_700_000_040, 0).unwrap() + Duration::minutes(idx);
+        Bar {
+            symbol: "btcusdt".to_string(),
+            start,
+            end: start + Duration::minutes(1),
+            open: close,
+            high,
+            low,
+            close,
+            volume: 1.0,
+            buy_volume: 0.0,
+            sell_volume: 0.0,
+            trades: 1,
+        }
+    }
+
+    fn run(spec: IndicatorSpec, bars: &[Bar]) -> BTreeMap<String, f64> {
+        let mut indicator = IndicatorRegistry::builtin().build(&spec).unwrap();
+        for bar in bars {
+            indicator.on_bar(bar);
+        }
+        let mut values = BTreeMap::new();
+        indicator.values(&mut IndicatorValues::new(spec.name(), &mut values));
+        values
+    }
+
+    fn closes(values: &[f64]) -> Vec<Bar> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(idx, &close)| bar(idx as i64, close, close, close))
+            .collect()
+    }
+
+    #[test]
+    fn moving_averages_and_rate_of_change() {
+        let bars = closes(&[10.0, 11.0, 12.0, 13.0, 14.0]);
+        let spec = IndicatorSpec::new("sma").with_param("period", 3);
+        assert_eq!(run(spec, &bars)["sma"], 13.0);
+
+        let spec = IndicatorSpec::new("ema")
+            .with_name("ema_fast")
+            .with_param("period", 3);
+        // Seeded at 11, then 11 + 0.5 * (13 - 11) = 12, then 13.
+        assert_eq!(run(spec, &bars)["ema_fast"], 13.0);
+
+        let spec = IndicatorSpec::new("roc").with_param("period", 4);
+        assert!((run(spec, &bars)["roc"] - 40.0).abs() < 1e-9);
+
+        // Not enough bars yet: nothing is published.
+        let spec = IndicatorSpec::new("sma").with_param("period", 10);
+        assert!(run(spec, &bars).is_empty());
+    }
+
+    #[test]
+    fn stochastic_publishes_named_fields() {
+        let bars = [
+            bar(0, 12.0, 8.0, 10.0),
+            bar(1, 14.0, 9.0, 13.0),
+            bar(2, 13.0, 10.0, 12.0),
+        ];
+        let spec = IndicatorSpec::new("stochastic")
+            .with_name("stoch")
+            .with_param("period", 2)
+            .with_param("smooth", 2);
+        let values = run(spec, &bars);
+        // k: (13 - 8) / (14 - 8) = 83.3, then (12 - 9) / (14 - 9) = 60.
+        assert!((values["stoch"] - 60.0).abs() < 1e-9);
+        assert!((values["stoch.d"] - (250.0 / 3.0 + 60.0) / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn calculator_kinds_publish_their_fields() {
+        let bars: Vec<Bar> = (0..40)
+            .map(|idx| {
+                let close = 100.0 + (idx as f64 * 0.7).sin() * 3.0;
+                bar(idx, close + 1.0, close - 1.0, close)
+            })
+            .collect();
+
+        let values = run(IndicatorSpec::new("macd"), &bars);
+        assert!(
+            (values["macd.histogram"] - (values["macd"] - values["macd.signal"])).abs() < 1e-12
+        );
+
+        let spec = IndicatorSpec::new("bollinger")
+            .with_param("period", 10)
+            .with_param("width", 1.5);
+        let values = run(spec, &bars);
+        assert!(values["bollinger.upper"] > values["bollinger"]);
+        assert!(values["bollinger.lower"] < values["bollinger"]);
+
+        let values = run(IndicatorSpec::new("ema_cross"), &bars);
+        assert!(
+            (values["ema_cross"] - (values["ema_cross.fast"] - values["ema_cross.slow"])).abs()
+                < 1e-12
+        );
+        // Every bar spans 2 around its close and the closes move less than that.
+        assert!((run(IndicatorSpec::new("atr"), &bars)["atr"] - 2.0).abs() < 0.5);
+
+        let spec = IndicatorSpec::new("vwap").with_param("timezone", "America/New_York");
+        let values = run(spec, &bars);
+        assert!((values["vwap"] - 100.0).abs() < 3.0);
+        assert!(values.contains_key("vwap.deviation_pct"));
+    }
+
+    #[test]
+    fn rejects_unknown_kinds_and_bad_params() {
+        let registry = IndicatorRegistry::builtin();
+        assert!(registry.build(&IndicatorSpec::new("ichimoku")).is_err());
+        assert!(
+            registry
+                .build(&IndicatorSpec::new("sma").with_param("period", 0))
+                .is_err()
+        );
+        assert!(
+            registry
+                .build(&IndicatorSpec::new("roc").with_param("period", "ten"))
+                .is_err()
+        );
+        assert!(
+            registry
+                .build(&IndicatorSpec::new("bollinger").with_param("width", 0))
+                .is_err()
+        );
+        assert!(
+            registry
+                .build(&IndicatorSpec::new("vwap").with_param("timezone", "Mars/Olympus"))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use tokio::{
+    sync::mpsc::Sender,
+    time::{MissedTickBehavior, interval},
+};
+use tracing::info;
+
+use crate::{
+    data::source::MarketSource,
+    models::{AssetDescriptor, PriceTick, SourceSpec, TradeSide},
+};
+
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0;
+
+/// Generates synthetic ticks from a stochastic price model, for offline demos and load tests.
+///
+/// Source options (all optional):
+/// - `model`: `gbm` (default), `jump` (Merton jump-diffusion) or `ou` (mean-reverting log price)
+/// - `price`: starting price, default `100`
+/// - `drift` / `volatility`: annualized drift and volatility, default `0` / `0.8`
+/// - `jump_rate`: expected jumps per minute, `jump_mean` / `jump_std`: jump log-return
+/// - `mean` / `reversion`: OU target price and reversion speed per second
+/// - `tick_rate`: ticks per second, default `5`
+/// - `volume` / `volume_sigma`: median trade size and its log-normal spread
+/// - `seed`: fixes the RNG so runs are reproducible
+/// - `script`: `;`-separated regimes `<kind>@<start>s:<arg>/<duration>s`, where kind is
+///   `shift` (`-20%` total move), `vol` or `volume` (`x4` multiplier),
+///   e.g. `shift@60s:-20%/10s;vol@60s:x5/120s` for a crash at t=60s
+#[derive(Debug, Clone, Default)]
+pub struct SimulatorSource;
+
+impl SimulatorSource {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl MarketSource for SimulatorSource {
+    fn name(&self) -> &str {
+        "simulator"
+    }
+
+    async fn run(&self, asset: AssetDescriptor, tx: Sender<PriceTick>) -> anyhow::Result<()> {
+        let config = SimulatorConfig::from_spec(&asset.source)?;
+        info!(symbol = %asset.symbol, model = ?config.model, "Starting market simulator");
+
+        let mut ticker = interval(Duration::from_secs_f64(1.0 / config.tick_rate));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut simulator = MarketSimulator::new(asset.symbol.clone(), config);
+
+        loop {
+            ticker.tick().await;
+            let tick = simulator.next_tick(Utc::now());
+            if tx.send(tick).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum PriceModel {
+    GeometricBrownian,
+    JumpDiffusion,
+    OrnsteinUhlenbeck,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulatorConfig {
+    pub model: PriceModel,
+    pub price: f64,
+    pub drift: f64,
+    pub volatility: f64,
+    pub jump_rate: f64,
+    pub jump_mean: f64,
+    pub jump_std: f64,
+    pub mean: f64,
+    pub reversion: f64,
+    pub tick_rate: f64,
+    pub volume: f64,
+    pub volume_sigma: f64,
+    pub seed: Option<u64>,
+    pub regimes: Vec<Regime>,
+}
+
+impl Default for SimulatorConfig {
+    fn default() -> Self {
+        Self {
+            model: PriceModel::GeometricBrownian,
+            price: 100.0,
+            drift: 0.0,
+            volatility: 0.8,
+            jump_rate: 0.5,
+            jump_mean: 0.0,
+            jump_std: 0.02,
+            mean: 100.0,
+            reversion: 0.05,
+            tick_rate: 5.0,
+            volume: 1.0,
+            volume_sigma: 0.75,
+            seed: None,
+            regimes: Vec::new(),
+        }
+    }
+}
+
+impl SimulatorConfig {
+    pub fn from_spec(spec: &SourceSpec) -> anyhow::Result<Self> {
+        let defaults = Self::default();
+        let model = match spec.option("model").unwrap_or("gbm") {
+            "gbm" => PriceModel::GeometricBrownian,
+            "jump" => PriceModel::JumpDiffusion,
+            "ou" => PriceModel::OrnsteinUhlenbeck,
+            other => anyhow::bail!("unknown simulator model {other}"),
+        };
+        let price = spec.parse_option("price").unwrap_or(defaults.price);
+        let regimes = match spec.option("script") {
+            Some(script) => parse_script(script)?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            model,
+            price,
+            drift: spec.parse_option("drift").unwrap_or(defaults.drift),
+            volatility: spec
+                .parse_option("volatility")
+                .unwrap_or(defaults.volatility),
+            jump_rate: spec.parse_option("jump_rate").unwrap_or(defaults.jump_rate),
+            jump_mean: spec.parse_option("jump_mean").unwrap_or(defaults.jump_mean),
+            jump_std: spec.parse_option("jump_std").unwrap_or(defaults.jump_std),
+            mean: spec.parse_option("mean").unwrap_or(price),
+            reversion: spec.parse_option("reversion").unwrap_or(defaults.reversion),
+            tick_rate: spec
+                .parse_option("tick_rate")
+                .unwrap_or(defaults.tick_rate)
+                .max(0.01),
+            volume: spec.parse_option("volume").unwrap_or(defaults.volume),
+            volume_sigma: spec
+                .parse_option("volume_sigma")
+                .unwrap_or(defaults.volume_sigma),
+            seed: spec.parse_option("seed"),
+            regimes,
+        })
+    }
+}
+
+/// A scripted market condition active for `duration` seconds from `start`.
+#[derive(Debug, Clone)]
+pub struct Regime {
+    pub start: f64,
+    pub duration: f64,
+    pub effect: RegimeEffect,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum RegimeEffect {
+    /// Total fractional price move spread evenly over the regime, e.g. `-0.2`.
+    Shift(f64),
+    VolatilityScale(f64),
+    VolumeScale(f64),
+}
+
+impl Regime {
+    fn is_active(&self, elapsed: f64) -> bool {
+        elapsed >= self.start && elapsed < self.start + self.duration
+    }
+}
+
+fn parse_script(script: &str) -> anyhow::Result<Vec<Regime>> {
+    script
+        .split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| parse_regime(entry).with_context(|| format!("invalid regime `{entry}`")))
+        .collect()
+}
+
+fn parse_regime(entry: &str) -> anyhow::Result<Regime> {
+    let (kind, rest) = entry.split_once('@').context("missing `@<start>`")?;
+    let (start, rest) = rest.split_once(':').context("missing `:<arg>`")?;
+    let (arg, duration) = rest.split_once('/').context("missing `/<duration>`")?;
+    let seconds =
+        |raw: &str| -> anyhow::Result<f64> { Ok(raw.trim().trim_end_matches('s').parse()?) };
+    let multiplier =
+        |raw: &str| -> anyhow::Result<f64> { Ok(raw.trim().trim_start_matches('x').parse()?) };
+
+    let effect = match kind.trim() {
+        "shift" => {
+            let pct: f64 = arg.trim().trim_end_matches('%').parse()?;
+            RegimeEffect::Shift(pct / 100.0)
+        }
+        "vol" => RegimeEffect::VolatilityScale(multiplier(arg)?),
+        "volume" => RegimeEffect::VolumeScale(multiplier(arg)?),
+        other => anyhow::bail!("unknown regime kind {other}"),
+    };
+
+    Ok(Regime {
+        start: seconds(start)?,
+        duration: seconds(duration)?.max(f64::EPSILON),
+        effect,
+    })
+}
+
+/// Steps a price model one tick at a time; separated from the source so load
+/// tests can drive it without a runtime.
+pub struct MarketSimulator {
+    symbol: String,
+    config: SimulatorConfig,
+    rng: StdRng,
+    log_price: f64,
+    elapsed: f64,
+}
+
+impl MarketSimulator {
+    pub fn new(symbol: impl Into<String>, config: SimulatorConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self {
+            symbol: symbol.into(),
+            log_price: config.price.max(f64::MIN_POSITIVE).ln(),
+            config,
+            rng,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn next_tick(&mut self, timestamp: DateTime<Utc>) -> PriceTick {
+        let dt = 1.0 / self.config.tick_rate;
+        let dt_years = dt / SECONDS_PER_YEAR;
+
+        let mut sigma = self.config.volatility;
+        let mut volume_scale = 1.0;
+        let mut shift = 0.0;
+        for regime in self
+            .config
+            .regimes
+            .iter()
+            .filter(|r| r.is_active(self.elapsed))
+        {
+            match regime.effect {
+                RegimeEffect::Shift(pct) => {
+                    shift += (1.0 + pct).max(f64::MIN_POSITIVE).ln() * dt / regime.duration;
+                }
+                RegimeEffect::VolatilityScale(scale) => sigma *= scale,
+                RegimeEffect::VolumeScale(scale) => volume_scale *= scale,
+            }
+        }
+
+        let diffusion = sigma * dt_years.sqrt() * self.standard_normal();
+        let step = match self.config.model {
+            PriceModel::GeometricBrownian => {
+                (self.config.drift - 0.5 * sigma * sigma) * dt_years + diffusion
+            }
+            PriceModel::JumpDiffusion => {
+                let jump_probability = self.config.jump_rate / 60.0 * dt;
+                let jump = if self.rng.r#gen::<f64>() < jump_probability {
+                    self.config.jump_mean + self.config.jump_std * self.standard_normal()
+                } else {
+                    0.0
+                };
+                (self.config.drift - 0.5 * sigma * sigma) * dt_years + diffusion + jump
+            }
+            PriceModel::OrnsteinUhlenbeck => {
+                let target = self.config.mean.max(f64::MIN_POSITIVE).ln();
+                self.config.reversion * (target - self.log_price) * dt + diffusion
+            }
+        };
+
+        self.log_price += step + shift;
+        self.elapsed += dt;
+
+        let volume = self.config.volume
+            * volume_scale
+            * (self.config.volume_sigma * self.standard_normal()).exp();
+
+        PriceTick {
+            symbol: self.symbol.clone(),
+            price: self.log_price.exp(),
+            volume,
+            timestamp,
+            side: Some(if step + shift >= 0.0 {
+                TradeSide::Buy
+            } else {
+                TradeSide::Sell
+            }),
+        }
+    }
+
+    /// Box-Muller transform; avoids pulling in `rand_distr` for one distribution.
+    fn standard_normal(&mut self) -> f64 {
+        let u1: f64 = self.rng.r#gen::<f64>().max(f64::MIN_POSITIVE);
+        let u2: f64 = self.rng.r#gen();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
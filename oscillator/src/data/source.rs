@@ -0,0 +1,79 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use chrono::Duration;
+
+use crate::{
+    data::streams::TickSender,
+    models::{AssetDescriptor, PriceTick},
+};
+
+/// A market data feed that pushes `PriceTick`s for one asset into a pipeline channel.
+///
+/// Implementations own their reconnect/poll loop; `run` only returns once the feed
+/// gives up or the receiving pipeline has gone away.
+#[async_trait]
+pub trait MarketSource: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn run(&self, asset: AssetDescriptor, tx: TickSender) -> anyhow::Result<()>;
+
+    /// Recent closed bars as ticks, oldest first, used to warm the indicators
+    /// before live data arrives. Sources without a history endpoint return
+    /// nothing.
+    async fn history(
+        &self,
+        _asset: &AssetDescriptor,
+        _request: &HistoryRequest,
+    ) -> anyhow::Result<Vec<PriceTick>> {
+        Ok(Vec::new())
+    }
+}
+
+/// How much history warm-up asks a source for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryRequest {
+    /// Closed primary bars to seed.
+    pub bars: usize,
+    /// Length of the asset's primary bars.
+    pub bar_duration: Duration,
+    /// How far back the history should reach so the longest timeframe is
+    /// seeded too. Never shorter than `bars` primary bars.
+    pub span: Duration,
+}
+
+impl HistoryRequest {
+    /// `bars` closed bars of both the primary bars and the `longest` timeframe.
+    pub fn new(bars: usize, bar_duration: Duration, longest: Duration) -> Self {
+        let count = i32::try_from(bars).unwrap_or(i32::MAX);
+        Self {
+            bars,
+            bar_duration,
+            span: bar_duration.max(longest) * count,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct SourceRegistry {
+    sources: HashMap<String, Arc<dyn MarketSource>>,
+}
+
+impl SourceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, source: Arc<dyn MarketSource>) -> &mut Self {
+        self.sources.insert(source.name().to_string(), source);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn MarketSource>> {
+        self.sources.get(name).cloned()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.sources.keys().map(String::as_str)
+    }
+}
@@ -1,21 +1,37 @@
 use std::{sync::Arc, time::Duration};
 
-use chrono::{TimeZone, Utc};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
 use reqwest::Client;
 use serde::Deserialize;
-use tokio::{
-    sync::mpsc::Sender,
-    task::JoinHandle,
-    time::{interval, sleep},
+use tokio::{sync::mpsc::Sender, task::JoinHandle, time::sleep};
+use tracing::{debug, info, warn};
+
+use crate::{
+    data::{
+        backoff::ReconnectPolicy,
+        calendar::TradingCalendar,
+        health::FeedHealthStore,
+        source::{HistoryRequest, MarketSource},
+    },
+    models::{AssetCategory, AssetDescriptor, FeedState, MarketSession, PriceTick},
 };
-use tracing::{info, warn};
 
-use crate::models::PriceTick;
+const DEFAULT_POLL_SECS: u64 = 15;
+/// Poll interval floor in pre- and post-market, when bars arrive sparsely.
+const EXTENDED_HOURS_POLL: Duration = Duration::from_secs(60);
+/// Width of the `interval=1m` chart bars.
+const BAR_SECS: i64 = 60;
+/// Chart range fetched for warm-up history; spans weekends and holidays.
+const HISTORY_RANGE: &str = "5d";
 
 #[derive(Clone)]
 pub struct YahooFinanceClient {
     base_url: Arc<String>,
     client: Client,
+    calendar: Option<TradingCalendar>,
+    health: FeedHealthStore,
+    reconnect: ReconnectPolicy,
 }
 
 impl YahooFinanceClient {
@@ -26,9 +42,30 @@ impl YahooFinanceClient {
                 .user_agent("oscillator/0.1")
                 .build()
                 .expect("reqwest client"),
+            calendar: None,
+            health: FeedHealthStore::new(),
+            reconnect: ReconnectPolicy::default(),
         }
     }
 
+    /// Reports poll successes, failures and market-closed pauses to `health`.
+    pub fn with_health(mut self, health: FeedHealthStore) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// Backoff applied on top of the poll interval after failed polls.
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    /// Pauses polling of stock assets while `calendar` says the exchange is closed.
+    pub fn with_calendar(mut self, calendar: TradingCalendar) -> Self {
+        self.calendar = Some(calendar);
+        self
+    }
+
     pub fn spawn_price_poller(
         &self,
         symbol: impl Into<String>,
@@ -38,65 +75,171 @@ impl YahooFinanceClient {
         let symbol = symbol.into();
         let client = self.clone();
         tokio::spawn(async move {
-            let mut ticker = interval(Duration::from_secs(interval_secs));
-            loop {
-                ticker.tick().await;
-                match client.fetch_latest(&symbol).await {
-                    Ok(tick) => {
+            let calendar = client.calendar.clone();
+            client
+                .poll_prices(&symbol, &tx, interval_secs, calendar.as_ref())
+                .await
+        })
+    }
+
+    async fn poll_prices(
+        &self,
+        symbol: &str,
+        tx: &Sender<PriceTick>,
+        interval_secs: u64,
+        calendar: Option<&TradingCalendar>,
+    ) {
+        let poll = Duration::from_secs(interval_secs);
+        // Newest bar already handed to the pipeline. `None` until the first
+        // successful fetch, which replays the session so far to warm indicators.
+        let mut last_emitted: Option<DateTime<Utc>> = None;
+        let mut backoff = self.reconnect.backoff();
+        loop {
+            match self.fetch_bars(symbol, "1d").await {
+                Ok(bars) => {
+                    self.health
+                        .set_state(self.name(), symbol, FeedState::Connected);
+                    backoff.reset();
+                    let fresh = unseen_bars(bars, last_emitted, Utc::now());
+                    if last_emitted.is_none() && !fresh.is_empty() {
+                        info!(%symbol, bars = fresh.len(), "Backfilling Yahoo session");
+                    }
+                    for tick in fresh {
+                        last_emitted = Some(tick.timestamp);
                         if tx.send(tick).await.is_err() {
-                            break;
+                            return;
                         }
                     }
-                    Err(err) => {
-                        warn!(%symbol, %err, "Yahoo poll failed - backing off");
-                        sleep(Duration::from_secs(5)).await;
-                    }
+                }
+                Err(err) => {
+                    warn!(%symbol, %err, "Yahoo poll failed");
+                    self.health.record_error(self.name(), symbol, &err);
+                    backoff
+                        .wait(&format!("Yahoo {symbol}"), |backoff| {
+                            self.health.record_backoff(self.name(), symbol, backoff)
+                        })
+                        .await;
                 }
             }
-        })
+
+            // Once the last session is backfilled there is nothing new until the
+            // exchange reopens, so sleep through nights, weekends and holidays.
+            // Extended hours trade thinly, so they are polled more slowly.
+            let now = Utc::now();
+            let delay = match calendar.map(|calendar| (calendar, calendar.session_at(now))) {
+                Some((calendar, MarketSession::Closed)) if last_emitted.is_some() => {
+                    let reopen = calendar.next_open(now);
+                    info!(%symbol, %reopen, "Market closed - pausing Yahoo polling");
+                    self.health
+                        .set_state(self.name(), symbol, FeedState::Paused);
+                    (reopen - now).to_std().unwrap_or_default()
+                }
+                Some((_, MarketSession::PreMarket | MarketSession::PostMarket)) => {
+                    poll.max(EXTENDED_HOURS_POLL)
+                }
+                _ => poll,
+            };
+            tokio::select! {
+                _ = sleep(delay) => {}
+                _ = tx.closed() => return,
+            }
+        }
     }
 
-    async fn fetch_latest(&self, symbol: &str) -> anyhow::Result<PriceTick> {
-        let url = format!(
-            "{}/v8/finance/chart/{}?interval=1m&range=1d",
-            self.base_url, symbol
-        );
-        info!(%symbol, "Fetching Yahoo chart");
+    /// Every bar in `range` (e.g. `1d`) with both a close and a volume, oldest first.
+    async fn fetch_bars(&self, symbol: &str, range: &str) -> anyhow::Result<Vec<PriceTick>> {
+        let url = chart_url(&self.base_url, symbol, range);
+        debug!(%symbol, "Fetching Yahoo chart");
         let resp: ChartResponse = self.client.get(url).send().await?.json().await?;
         let result = resp
             .chart
             .result
-            .get(0)
+            .first()
             .ok_or_else(|| anyhow::anyhow!("empty chart result"))?;
         let quote = result
             .indicators
             .quote
-            .get(0)
+            .first()
             .ok_or_else(|| anyhow::anyhow!("missing quote block"))?;
 
-        let mut latest = None;
+        let mut bars: Vec<PriceTick> = result
+            .timestamp
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, ts)| {
+                let price = (*quote.close.get(idx)?)?;
+                let volume = (*quote.volume.get(idx)?)?;
+                let timestamp = Utc.timestamp_opt(*ts, 0).single()?;
+                Some(PriceTick {
+                    symbol: symbol.to_string(),
+                    price,
+                    volume,
+                    timestamp,
+                    side: None,
+                })
+            })
+            .collect();
+        anyhow::ensure!(!bars.is_empty(), "no data points");
+        bars.sort_by_key(|bar| bar.timestamp);
+        Ok(bars)
+    }
+}
 
-        for (idx, ts) in result.timestamp.iter().enumerate().rev() {
-            if let (Some(price), Some(volume)) = (quote.close.get(idx), quote.volume.get(idx)) {
-                if let (Some(price), Some(volume)) = (price, volume) {
-                    latest = Some((*ts, *price, *volume));
-                    break;
-                }
-            }
-        }
+/// One-minute chart for `symbol` over `range`, including pre- and post-market
+/// bars so extended sessions are not silent.
+fn chart_url(base_url: &str, symbol: &str, range: &str) -> String {
+    format!("{base_url}/v8/finance/chart/{symbol}?interval=1m&includePrePost=true&range={range}")
+}
 
-        let (ts, price, volume) = latest.ok_or_else(|| anyhow::anyhow!("no data points"))?;
-        let timestamp = Utc
-            .timestamp_opt(ts, 0)
-            .single()
-            .unwrap_or_else(|| Utc::now());
-
-        Ok(PriceTick {
-            symbol: symbol.to_string(),
-            price,
-            volume,
-            timestamp,
-        })
+/// Bars newer than `last_emitted` that have closed by `now`. Yahoo stamps bars
+/// with their open time and keeps revising the newest one, so it is held back
+/// until a full bar interval has passed rather than emitted with a partial close.
+fn unseen_bars(
+    bars: Vec<PriceTick>,
+    last_emitted: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Vec<PriceTick> {
+    let bar = chrono::Duration::seconds(BAR_SECS);
+    bars.into_iter()
+        .filter(|tick| last_emitted.is_none_or(|last| tick.timestamp > last))
+        .filter(|tick| tick.timestamp + bar <= now)
+        .collect()
+}
+
+#[async_trait]
+impl MarketSource for YahooFinanceClient {
+    fn name(&self) -> &str {
+        "yahoo"
+    }
+
+    async fn run(&self, asset: AssetDescriptor, tx: Sender<PriceTick>) -> anyhow::Result<()> {
+        let interval_secs = asset
+            .source
+            .parse_option("interval_secs")
+            .unwrap_or(DEFAULT_POLL_SECS);
+        let calendar = self
+            .calendar
+            .as_ref()
+            .filter(|_| asset.category == AssetCategory::Stock);
+        self.poll_prices(&asset.symbol, &tx, interval_secs, calendar)
+            .await;
+        Ok(())
+    }
+
+    /// Closed 1-minute bars from the last few sessions, enough to cover the
+    /// requested span where the range reaches that far.
+    async fn history(
+        &self,
+        asset: &AssetDescriptor,
+        request: &HistoryRequest,
+    ) -> anyhow::Result<Vec<PriceTick>> {
+        let bars = self.fetch_bars(&asset.symbol, HISTORY_RANGE).await?;
+        let mut closed = unseen_bars(bars, None, Utc::now());
+        let limit = usize::try_from(request.span.num_seconds() / BAR_SECS)
+            .unwrap_or_default()
+            .max(request.bars);
+        let skip = closed.len().saturating_sub(limit);
+        Ok(closed.split_off(skip))
     }
 }
 
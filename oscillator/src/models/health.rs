@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Connection state as last reported by an asset's market source.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedState {
+    Connecting,
+    Connected,
+    /// Deliberately idle, e.g. polling paused while the exchange is closed.
+    Paused,
+    Disconnected,
+    /// Too many consecutive failures; reconnects are suspended for a cooldown.
+    CircuitOpen,
+}
+
+/// Why the tick filter dropped a tick before it reached the indicators.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TickRejection {
+    /// Non-positive or non-finite price, or negative or non-finite volume.
+    Invalid,
+    /// Outside the rolling median ± MAD band.
+    Outlier,
+    /// Older than the last accepted tick.
+    OutOfOrder,
+    /// Identical to the last accepted tick.
+    Duplicate,
+}
+
+/// Ticks dropped by the tick filter, per reason.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct RejectionCounts {
+    pub invalid: u64,
+    pub outlier: u64,
+    pub out_of_order: u64,
+    pub duplicate: u64,
+}
+
+impl RejectionCounts {
+    pub fn record(&mut self, reason: TickRejection) {
+        let count = match reason {
+            TickRejection::Invalid => &mut self.invalid,
+            TickRejection::Outlier => &mut self.outlier,
+            TickRejection::OutOfOrder => &mut self.out_of_order,
+            TickRejection::Duplicate => &mut self.duplicate,
+        };
+        *count += 1;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedStatus {
+    pub symbol: String,
+    pub source: String,
+    pub state: FeedState,
+    /// Disconnected, circuit open, or expected to be ticking but silent past `stale_after_secs`.
+    pub degraded: bool,
+    pub last_tick: Option<DateTime<Utc>>,
+    pub last_tick_age_secs: Option<f64>,
+    /// Ticks per second over the most recent measurement window.
+    pub tick_rate: f64,
+    pub reconnects: u64,
+    pub errors: u64,
+    pub last_error: Option<String>,
+    pub rejected: RejectionCounts,
+    /// Ticks the source discarded because the pipeline was not keeping up.
+    pub dropped: u64,
+    pub stale_after_secs: f64,
+}
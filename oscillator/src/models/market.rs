@@ -1,3 +1,5 @@
+use std::{collections::BTreeMap, str::FromStr};
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -23,6 +25,81 @@ pub struct AssetDescriptor {
     pub category: AssetCategory,
     pub description: String,
     pub tick_size: f64,
+    pub source: SourceSpec,
+    /// Extra indicators to run on top of the built-in set, published under
+    /// `MarketMetrics::values`.
+    #[serde(default)]
+    pub indicators: Vec<IndicatorSpec>,
+}
+
+/// Names the `MarketSource` that feeds an asset plus any source-specific options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceSpec {
+    pub name: String,
+    #[serde(default)]
+    pub options: BTreeMap<String, String>,
+}
+
+impl SourceSpec {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            options: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_option(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.options.insert(key.into(), value.to_string());
+        self
+    }
+
+    pub fn option(&self, key: &str) -> Option<&str> {
+        self.options.get(key).map(String::as_str)
+    }
+
+    pub fn parse_option<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.option(key).and_then(|v| v.parse().ok())
+    }
+}
+
+/// One pluggable indicator for an asset: a `kind` registered in the
+/// `IndicatorRegistry`, its parameters, and the name its values are published
+/// under (the kind itself unless set).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicatorSpec {
+    pub kind: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+}
+
+impl IndicatorSpec {
+    pub fn new(kind: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            name: None,
+            params: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_param(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.params.insert(key.into(), value.to_string());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.kind)
+    }
+
+    pub fn parse_param<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.params.get(key).and_then(|v| v.parse().ok())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +115,34 @@ pub struct PriceTick {
     pub price: f64,
     pub volume: f64,
     pub timestamp: DateTime<Utc>,
+    /// Aggressor side of the trade, when the venue reports it.
+    #[serde(default)]
+    pub side: Option<TradeSide>,
+}
+
+/// OHLCV aggregate of the ticks in one fixed-length interval.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Bar {
+    pub symbol: String,
+    /// Interval start, aligned to a multiple of the bar duration since the epoch.
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    /// Aggressor volume from sided ticks; unsided ticks only count towards `volume`.
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    pub trades: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TradeSide {
+    Buy,
+    Sell,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -51,10 +156,240 @@ pub struct MarketMetrics {
     pub volatility: f64,
     pub tempo_bias: f64,
     pub last_updated: DateTime<Utc>,
+    #[serde(default)]
+    pub book: Option<BookMetrics>,
+    #[serde(default)]
+    pub flow: Option<TradeFlowMetrics>,
+    /// Exchange session for assets that follow a trading calendar; `None` for 24/7 markets.
+    #[serde(default)]
+    pub session: Option<MarketSession>,
+    /// Trend and band indicators; each stays `None` until enough bars have closed.
+    #[serde(default)]
+    pub macd: Option<MacdMetrics>,
+    #[serde(default)]
+    pub bollinger: Option<BollingerMetrics>,
+    #[serde(default)]
+    pub ema: Option<EmaMetrics>,
+    /// Average true range in price units.
+    #[serde(default)]
+    pub atr: Option<f64>,
+    #[serde(default)]
+    pub vwap: Option<VwapMetrics>,
+    /// Confirmed market regime; `None` until enough bars have closed.
+    #[serde(default)]
+    pub regime: Option<MarketRegime>,
+    /// Outputs of the asset's configured indicators, keyed by indicator name
+    /// (`sma_50`) or name and output (`stoch.d`).
+    #[serde(default)]
+    pub values: BTreeMap<String, f64>,
+    /// Where the live readings rank in the asset's own recent history; `None`
+    /// until enough bars have closed or when normalization is disabled.
+    #[serde(default)]
+    pub normalized: Option<NormalizedMetrics>,
+    /// The same trend indicators on the asset's longer timeframes, shortest
+    /// first; the top-level fields are the primary (shortest) timeframe.
+    #[serde(default)]
+    pub timeframes: Vec<TimeframeMetrics>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum AssetKind {
-    BinanceTrade { symbol: String },
-    YahooEquity { symbol: String },
+impl MarketMetrics {
+    pub fn value(&self, name: &str) -> Option<f64> {
+        self.values.get(name).copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketSession {
+    PreMarket,
+    Regular,
+    PostMarket,
+    Closed,
+}
+
+/// A discrete, notable moment in an asset's market, as opposed to the
+/// continuous metrics.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MarketEvent {
+    pub at: DateTime<Utc>,
+    /// Price when the event fired.
+    pub price: f64,
+    #[serde(flatten)]
+    pub kind: MarketEventKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MarketEventKind {
+    SessionHigh,
+    SessionLow,
+    /// Crossed a round-number price level.
+    RoundNumber {
+        level: f64,
+        direction: CrossDirection,
+    },
+    /// A closed bar's volume `sigma` standard deviations above the recent mean.
+    VolumeSpike {
+        volume: f64,
+        sigma: f64,
+    },
+    /// RSI crossed 30 or 70.
+    RsiCross {
+        level: f64,
+        direction: CrossDirection,
+    },
+    LargeTrade {
+        volume: f64,
+        side: Option<TradeSide>,
+    },
+    /// The first price after a reconnect moved `gap_pct` from the last one before it.
+    ReconnectGap {
+        gap_pct: f64,
+        gap_secs: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketRegime {
+    TrendingUp,
+    TrendingDown,
+    Ranging,
+    /// Volatility and volume jump out of their recent range.
+    Breakout,
+    /// A breakout to the downside on extreme volume.
+    Capitulation,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MacdMetrics {
+    /// Fast EMA minus slow EMA of closes.
+    pub line: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BollingerMetrics {
+    pub middle: f64,
+    pub upper: f64,
+    pub lower: f64,
+    /// Position of the price within the bands: `0` at the lower band, `1` at the upper.
+    pub percent_b: f64,
+    /// `(upper - lower) / middle`.
+    pub bandwidth: f64,
+    /// Bandwidth more than one deviation below its recent average.
+    pub squeeze: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossDirection {
+    /// Fast EMA crossed above the slow one.
+    Bullish,
+    Bearish,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EmaMetrics {
+    pub fast: f64,
+    pub slow: f64,
+    /// Set on the bar where the fast EMA crossed the slow one.
+    pub cross: Option<CrossDirection>,
+}
+
+/// Volume-weighted average price since the current session started.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct VwapMetrics {
+    pub vwap: f64,
+    /// Price distance from VWAP as a percentage of VWAP.
+    pub deviation_pct: f64,
+}
+
+/// Indicators over one longer bar timeframe.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TimeframeMetrics {
+    /// Bar length as a label, e.g. `15m` or `1h`.
+    pub timeframe: String,
+    pub bar_secs: i64,
+    /// Price change since the previous bar on this timeframe closed.
+    pub price_change_percent: f64,
+    pub rsi: f64,
+    pub volatility: f64,
+    #[serde(default)]
+    pub macd: Option<MacdMetrics>,
+    #[serde(default)]
+    pub ema: Option<EmaMetrics>,
+    #[serde(default)]
+    pub regime: Option<MarketRegime>,
+}
+
+impl TimeframeMetrics {
+    /// Direction of the trend on this timeframe: the regime while it is
+    /// trending, otherwise the MACD histogram's sign. `None` when flat or
+    /// still warming up.
+    pub fn trend(&self) -> Option<CrossDirection> {
+        match self.regime {
+            Some(MarketRegime::TrendingUp) => return Some(CrossDirection::Bullish),
+            Some(MarketRegime::TrendingDown | MarketRegime::Capitulation) => {
+                return Some(CrossDirection::Bearish);
+            }
+            _ => {}
+        }
+        let histogram = self.macd.as_ref()?.histogram;
+        if histogram > 0.0 {
+            Some(CrossDirection::Bullish)
+        } else if histogram < 0.0 {
+            Some(CrossDirection::Bearish)
+        } else {
+            None
+        }
+    }
+}
+
+/// A reading ranked against its rolling distribution.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+pub struct NormalizedValue {
+    /// Standard deviations from the rolling mean.
+    pub z_score: f64,
+    /// Share of the window below the reading, from 0 to 1.
+    pub percentile: f64,
+}
+
+/// The mapper's inputs normalized per asset, so a 2% move or a given
+/// volatility sounds as unusual as it is for that asset.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct NormalizedMetrics {
+    pub price: NormalizedValue,
+    pub price_change: NormalizedValue,
+    pub volatility: NormalizedValue,
+    pub volume_ratio: NormalizedValue,
+}
+
+/// Aggressor-side volume over the indicator window; only present once sided trades arrive.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TradeFlowMetrics {
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    /// Buy minus sell volume since the pipeline started.
+    pub cumulative_delta: f64,
+    /// `(buy - sell) / (buy + sell)` over the window, in `[-1, 1]`.
+    pub imbalance: f64,
+}
+
+/// Order book summary over the top N levels of each side.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BookMetrics {
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub spread: f64,
+    pub spread_bps: f64,
+    /// `(bid depth - ask depth) / total depth`, in `[-1, 1]`; positive means bid-heavy.
+    pub imbalance: f64,
+    /// Average price of the top levels weighted by resting quantity.
+    pub weighted_mid: f64,
+    pub bid_depth: f64,
+    pub ask_depth: f64,
+    /// Gaps between adjacent levels much wider than the typical level spacing.
+    pub liquidity_holes: usize,
 }
@@ -1,7 +1,12 @@
+pub mod health;
 pub mod market;
 pub mod musical;
 
+pub use health::{FeedState, FeedStatus, RejectionCounts, TickRejection};
 pub use market::{
-    AssetCategory, AssetDescriptor, AssetKind, AssetSelection, MarketMetrics, PriceTick,
+    AssetCategory, AssetDescriptor, AssetSelection, Bar, BollingerMetrics, BookMetrics,
+    CrossDirection, EmaMetrics, IndicatorSpec, MacdMetrics, MarketEvent, MarketEventKind,
+    MarketMetrics, MarketRegime, MarketSession, NormalizedMetrics, NormalizedValue, PriceTick,
+    SourceSpec, TimeframeMetrics, TradeFlowMetrics, TradeSide, VwapMetrics,
 };
-pub use musical::{AudioChunk, AudioPacket, HarmonyQuality, MusicalParams};
+pub use musical::{Accent, AudioChunk, AudioPacket, HarmonyQuality, MusicalParams};
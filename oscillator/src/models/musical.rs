@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::MarketMetrics;
+use super::{MarketEvent, MarketMetrics};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum HarmonyQuality {
@@ -21,6 +21,34 @@ pub struct MusicalParams {
     pub distortion: f32,
     pub volume_intensity: f64,
     pub style: String,
+    /// Stereo position from `-1.0` (left) to `1.0` (right).
+    #[serde(default)]
+    pub pan: f32,
+    /// Low-pass cutoff as a fraction of each style's usual cutoff.
+    #[serde(default = "full_brightness")]
+    pub brightness: f32,
+    /// One-shot hits marking market events since the previous chunk.
+    #[serde(default)]
+    pub accents: Vec<Accent>,
+    /// Chord roots to step through bar by bar, in semitones from the melody
+    /// root; empty holds one chord.
+    #[serde(default)]
+    pub progression: Vec<i32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Accent {
+    /// Cymbal crash on the downbeat.
+    Crash,
+    /// Filter sweep building across the chunk.
+    Riser,
+    /// Short two-note stab, stepping up or down.
+    Stinger { rising: bool },
+}
+
+fn full_brightness() -> f32 {
+    1.0
 }
 
 impl Default for MusicalParams {
@@ -34,6 +62,10 @@ impl Default for MusicalParams {
             distortion: 0.0,
             volume_intensity: 1.0,
             style: "Electronic".to_string(),
+            pan: 0.0,
+            brightness: 1.0,
+            accents: Vec::new(),
+            progression: Vec::new(),
         }
     }
 }
@@ -52,5 +84,8 @@ pub struct AudioPacket {
     pub asset: String,
     pub metrics: MarketMetrics,
     pub params: MusicalParams,
+    /// Market events detected since the previous packet.
+    #[serde(default)]
+    pub events: Vec<MarketEvent>,
     pub chunk: AudioChunk,
 }
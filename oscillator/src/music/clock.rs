@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::time::Instant;
+
+const BEATS_PER_BAR: f64 = 4.0;
+
+/// Length in seconds of a chunk of `bars` 4/4 bars at `tempo` BPM.
+pub fn chunk_seconds(bars: usize, tempo: f64) -> f64 {
+    bars as f64 * BEATS_PER_BAR * 60.0 / tempo.max(1.0)
+}
+
+/// Paces chunk rendering at one chunk per chunk duration, independent of tick rate.
+///
+/// The clock keeps a running stream position so consecutive chunks are stamped
+/// back-to-back. If rendering falls more than a chunk behind wall time the clock
+/// resyncs to now instead of bursting out the backlog.
+pub struct RenderClock {
+    bars_per_chunk: usize,
+    deadline: Instant,
+    stream_time: DateTime<Utc>,
+}
+
+impl RenderClock {
+    pub fn new(bars_per_chunk: usize) -> Self {
+        Self {
+            bars_per_chunk: bars_per_chunk.max(1),
+            deadline: Instant::now(),
+            stream_time: Utc::now(),
+        }
+    }
+
+    /// Restarts the clock so the next chunk is due immediately.
+    pub fn reset(&mut self) {
+        self.deadline = Instant::now();
+        self.stream_time = Utc::now();
+    }
+
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    pub fn chunk_duration(&self, tempo: f64) -> Duration {
+        Duration::from_secs_f64(chunk_seconds(self.bars_per_chunk, tempo))
+    }
+
+    /// Schedules the next chunk one chunk duration after the current one and
+    /// returns the stream timestamp at which the current chunk starts.
+    pub fn advance(&mut self, tempo: f64) -> DateTime<Utc> {
+        let duration = self.chunk_duration(tempo);
+        let now = Instant::now();
+        if now.saturating_duration_since(self.deadline) > duration {
+            self.reset();
+        }
+
+        let start = self.stream_time;
+        self.deadline += duration;
+        self.stream_time += chrono::Duration::from_std(duration).unwrap_or_default();
+        start
+    }
+}
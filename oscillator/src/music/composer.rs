@@ -1,13 +1,19 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use tunes::prelude::*;
 
-use crate::models::{AudioChunk, MusicalParams};
+use crate::models::{Accent, AudioChunk, MarketRegime, MusicalParams};
 
-use super::styles::CompositionStyle;
+use super::{
+    sections::{SectionPlanner, SongSection},
+    styles::CompositionStyle,
+};
 
+/// Renders one asset's chunks. Regime changes move the arrangement between song
+/// sections (see `SectionPlanner`); every style drops or adds parts per section.
 pub struct MarketComposer {
     sample_rate: u32,
     bars_per_chunk: usize,
+    sections: SectionPlanner,
 }
 
 impl MarketComposer {
@@ -15,14 +21,49 @@ impl MarketComposer {
         Self {
             sample_rate,
             bars_per_chunk,
+            sections: SectionPlanner::default(),
         }
     }
 
+    /// Picks the section the next chunk is arranged as.
+    pub fn next_section(&mut self, regime: Option<MarketRegime>) -> SongSection {
+        self.sections.next(regime)
+    }
+
+    pub fn section(&self) -> SongSection {
+        self.sections.current()
+    }
+
+    /// Starts the song over from the intro.
+    pub fn restart_song(&mut self) {
+        self.sections.restart();
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn bars_per_chunk(&self) -> usize {
+        self.bars_per_chunk
+    }
+
     pub fn render_chunk(
         &self,
         params: &MusicalParams,
         style: CompositionStyle,
+        timestamp: DateTime<Utc>,
     ) -> anyhow::Result<AudioChunk> {
+        let buffer = self.render_buffer(params, style)?;
+        Ok(self.encode_chunk(&buffer, timestamp))
+    }
+
+    /// Renders one chunk's composition to interleaved stereo `f32` samples,
+    /// including any effect tail that rings past the nominal chunk length.
+    pub fn render_buffer(
+        &self,
+        params: &MusicalParams,
+        style: CompositionStyle,
+    ) -> anyhow::Result<Vec<f32>> {
         let tempo = Tempo::new(params.tempo as f32);
         let mut comp = Composition::new(tempo);
 
@@ -31,23 +72,30 @@ impl MarketComposer {
             CompositionStyle::Ambient => self.compose_ambient(&mut comp, params),
             CompositionStyle::Orchestral => self.compose_orchestral(&mut comp, params),
             CompositionStyle::Rock => self.compose_rock(&mut comp, params),
+            CompositionStyle::MarketClosed => self.compose_closed(&mut comp, params),
         }
+        self.compose_accents(&mut comp, params);
 
         let mut mixer = comp.into_mixer();
-        let buffer = mixer.render_to_buffer(self.sample_rate as f32);
+        let mut buffer = mixer.render_to_buffer(self.sample_rate as f32);
+        apply_pan(&mut buffer, params.pan);
+        Ok(buffer)
+    }
+
+    pub fn encode_chunk(&self, buffer: &[f32], timestamp: DateTime<Utc>) -> AudioChunk {
         let frames = buffer.len() / 2;
         let mut samples = Vec::with_capacity(buffer.len() * std::mem::size_of::<f32>());
         for sample in buffer {
             samples.extend_from_slice(&sample.to_le_bytes());
         }
 
-        Ok(AudioChunk {
+        AudioChunk {
             samples,
             frames,
             channels: 2,
             sample_rate: self.sample_rate,
-            timestamp: Utc::now(),
-        })
+            timestamp,
+        }
     }
 
     fn compose_electronic(&self, comp: &mut Composition, params: &MusicalParams) {
@@ -56,32 +104,37 @@ impl MarketComposer {
 
         let mut lead = comp.instrument("lead", &Instrument::synth_lead());
         lead = lead
-            .filter(Filter::low_pass(2400.0, 0.7))
+            .filter(Filter::low_pass(2400.0 * params.brightness, 0.7))
             .reverb(Reverb::new(0.4, 0.6, params.reverb_mix))
             .delay(Delay::new(0.375, 0.3, 0.4));
 
         for _ in 0..self.bars_per_chunk {
             for note in &params.melody_notes {
-                lead = lead.note(&[*note], quarter * 0.5).wait(0.05);
+                lead = lead.note(&self.voicing(*note), quarter * 0.5).wait(0.05);
             }
         }
 
-        let mut bass = comp.instrument("bass", &Instrument::sub_bass());
-        bass = bass.distortion(Distortion::new(
-            1.0 + params.distortion * 4.0,
-            params.distortion,
-        ));
+        let section = self.section();
+        if section.has_bass() {
+            let mut bass = comp.instrument("bass", &Instrument::sub_bass());
+            bass = bass.distortion(Distortion::new(
+                1.0 + params.distortion * 4.0,
+                params.distortion,
+            ));
 
-        for _ in 0..self.bars_per_chunk {
-            bass = bass.note(&[params.bass_note], quarter);
+            for bar in 0..self.bars_per_chunk {
+                bass = bass.note(&[params.bass_note * self.chord_shift(params, bar)], quarter);
+            }
         }
 
-        let kick_pattern = self.kick_pattern(params.volume_intensity);
-        comp.track("drums")
-            .drum_grid(16, sixteenth)
-            .kick(&kick_pattern)
-            .snare(&[4, 12])
-            .hihat(&[2, 6, 10, 14]);
+        if section.has_drums() {
+            let kick_pattern = self.kick_pattern(params.volume_intensity);
+            comp.track("drums")
+                .drum_grid(16, sixteenth)
+                .kick(&kick_pattern)
+                .snare(&[4, 12])
+                .hihat(&[2, 6, 10, 14]);
+        }
     }
 
     fn compose_orchestral(&self, comp: &mut Composition, params: &MusicalParams) {
@@ -90,29 +143,37 @@ impl MarketComposer {
 
         let mut strings = comp.instrument("strings", &Instrument::strings());
         strings = strings
-            .filter(Filter::low_pass(2200.0, 0.8))
+            .filter(Filter::low_pass(2200.0 * params.brightness, 0.8))
             .reverb(Reverb::new(0.6, 0.5, (params.reverb_mix * 1.2).min(1.0)));
 
         for _ in 0..self.bars_per_chunk {
             for note in &params.melody_notes {
-                strings = strings.notes(&[*note, *note * 1.25], half);
+                let mut voicing = self.voicing(*note);
+                voicing.push(*note * 1.25);
+                strings = strings.notes(&voicing, half);
             }
         }
 
-        let mut brass = comp.instrument("brass", &Instrument::brass());
-        brass = brass.tremolo(Tremolo::new(
-            quarter.recip(),
-            (params.distortion + 0.2).min(0.8),
-        ));
+        let section = self.section();
+        if section.has_bass() {
+            let mut brass = comp.instrument("brass", &Instrument::brass());
+            brass = brass.tremolo(Tremolo::new(
+                quarter.recip(),
+                (params.distortion + 0.2).min(0.8),
+            ));
 
-        for _ in 0..self.bars_per_chunk {
-            brass = brass.note(&[params.bass_note * 0.5], quarter * 2.0);
+            for bar in 0..self.bars_per_chunk {
+                let root = params.bass_note * 0.5 * self.chord_shift(params, bar);
+                brass = brass.note(&[root], quarter * 2.0);
+            }
         }
 
-        comp.track("timpani")
-            .drum_grid(8, quarter)
-            .kick(&[0, 4])
-            .snare(&[2, 6]);
+        if section.has_drums() {
+            comp.track("timpani")
+                .drum_grid(8, quarter)
+                .kick(&[0, 4])
+                .snare(&[2, 6]);
+        }
     }
 
     fn compose_ambient(&self, comp: &mut Composition, params: &MusicalParams) {
@@ -120,12 +181,18 @@ impl MarketComposer {
 
         let mut pad = comp.instrument("pad", &Instrument::ambient_pad());
         pad = pad
-            .filter(Filter::low_pass(1800.0, 0.9))
+            .filter(Filter::low_pass(1800.0 * params.brightness, 0.9))
             .reverb(Reverb::new(0.8, 0.7, (params.reverb_mix + 0.2).min(1.0)))
             .chorus(Chorus::new(0.3, 0.002, 0.4));
 
-        for _ in 0..self.bars_per_chunk {
-            pad = pad.notes(&self.pad_chord(params), whole);
+        let mut chord = self.pad_chord(params);
+        if self.section().doubles_melody() {
+            chord.push(chord[0] * 2.0);
+        }
+        for bar in 0..self.bars_per_chunk {
+            let shift = self.chord_shift(params, bar);
+            let voiced: Vec<f32> = chord.iter().map(|note| note * shift).collect();
+            pad = pad.notes(&voiced, whole);
         }
     }
 
@@ -140,22 +207,97 @@ impl MarketComposer {
 
         for _ in 0..self.bars_per_chunk {
             for note in &params.melody_notes {
-                guitar = guitar.note(&[*note], quarter * 0.5);
+                guitar = guitar.note(&self.voicing(*note), quarter * 0.5);
             }
         }
 
-        let mut bass = comp.instrument("bass_guitar", &Instrument::funk_bass());
-        for _ in 0..self.bars_per_chunk {
-            bass = bass.note(&[params.bass_note], quarter);
+        let section = self.section();
+        if section.has_bass() {
+            let mut bass = comp.instrument("bass_guitar", &Instrument::funk_bass());
+            for bar in 0..self.bars_per_chunk {
+                bass = bass.note(&[params.bass_note * self.chord_shift(params, bar)], quarter);
+            }
         }
 
-        let sixteen = comp.tempo().sixteenth_note();
-        let hats: Vec<usize> = (0..16).step_by(2).collect();
-        comp.track("rock_drums")
-            .drum_grid(16, sixteen)
-            .kick(&[0, 8])
-            .snare(&[4, 12])
-            .hihat(&hats);
+        if section.has_drums() {
+            let sixteen = comp.tempo().sixteenth_note();
+            let hats: Vec<usize> = (0..16).step_by(2).collect();
+            comp.track("rock_drums")
+                .drum_grid(16, sixteen)
+                .kick(&[0, 8])
+                .snare(&[4, 12])
+                .hihat(&hats);
+        }
+    }
+
+    /// A dark, held drone with no rhythm section, so a shut market sounds parked
+    /// rather than like a frozen quote on loop.
+    fn compose_closed(&self, comp: &mut Composition, params: &MusicalParams) {
+        let whole = comp.tempo().whole_note();
+        let held = whole * self.bars_per_chunk as f32;
+
+        comp.instrument("closed_drone", &Instrument::sub_bass())
+            .filter(Filter::low_pass(400.0, 0.5))
+            .reverb(Reverb::new(0.9, 0.8, params.reverb_mix))
+            .notes(&[params.bass_note, params.bass_note * 1.5], held);
+
+        let mut pad = comp.instrument("closed_pad", &Instrument::ambient_pad());
+        pad = pad
+            .filter(Filter::low_pass(900.0, 0.9))
+            .reverb(Reverb::new(0.95, 0.8, params.reverb_mix))
+            .chorus(Chorus::new(0.1, 0.003, 0.5));
+        for note in &params.melody_notes {
+            pad = pad.notes(&[*note, *note * 1.5], held);
+        }
+    }
+
+    /// A melody note, doubled an octave up in the chorus.
+    fn voicing(&self, note: f32) -> Vec<f32> {
+        if self.section().doubles_melody() {
+            vec![note, note * 2.0]
+        } else {
+            vec![note]
+        }
+    }
+
+    /// One-shot hits for market events, layered over whatever style is playing.
+    fn compose_accents(&self, comp: &mut Composition, params: &MusicalParams) {
+        let quarter = comp.tempo().quarter_note();
+        let held = comp.tempo().whole_note() * self.bars_per_chunk as f32;
+        let root = params
+            .melody_notes
+            .first()
+            .copied()
+            .unwrap_or(params.bass_note * 4.0);
+
+        for accent in &params.accents {
+            match accent {
+                Accent::Crash => {
+                    comp.track("accent_crash").drum_grid(4, quarter).crash(&[0]);
+                }
+                Accent::Riser => {
+                    comp.instrument("accent_riser", &Instrument::riser())
+                        .note(&[root], held);
+                }
+                Accent::Stinger { rising } => {
+                    let target = if *rising { root * 1.5 } else { root * 0.75 };
+                    comp.instrument("accent_stinger", &Instrument::impact())
+                        .note(&[root], quarter * 0.5)
+                        .note(&[target], quarter * 0.5);
+                }
+            }
+        }
+    }
+
+    /// Frequency ratio of the progression's chord for `bar` of this chunk. The
+    /// progression restarts with each song section, so it spans chunks.
+    fn chord_shift(&self, params: &MusicalParams, bar: usize) -> f32 {
+        if params.progression.is_empty() {
+            return 1.0;
+        }
+        let position = self.sections.chunk_in_section() * self.bars_per_chunk + bar;
+        let semitones = params.progression[position % params.progression.len()];
+        2f32.powf(semitones as f32 / 12.0)
     }
 
     fn pad_chord(&self, params: &MusicalParams) -> Vec<f32> {
@@ -176,3 +318,39 @@ impl MarketComposer {
         }
     }
 }
+
+/// Equal-power pan of an interleaved stereo buffer: the summed power stays
+/// constant, so the centre sits 3 dB down per channel and a hard pan carries
+/// the signal at unity gain.
+fn apply_pan(buffer: &mut [f32], pan: f32) {
+    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+    let (left, right) = (angle.cos(), angle.sin());
+    for frame in buffer.chunks_exact_mut(2) {
+        frame[0] *= left;
+        frame[1] *= right;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn panned(pan: f32) -> (f32, f32) {
+        let mut frame = [1.0, 1.0];
+        apply_pan(&mut frame, pan);
+        (frame[0], frame[1])
+    }
+
+    #[test]
+    fn pan_keeps_power_constant_without_boosting() {
+        for pan in [-1.0, -0.5, -0.01, 0.0, 0.01, 0.5, 1.0] {
+            let (left, right) = panned(pan);
+            assert!((left * left + right * right - 1.0).abs() < 1e-6, "{pan}");
+            assert!(left <= 1.0 + 1e-6 && right <= 1.0 + 1e-6, "{pan}");
+        }
+        let (left, right) = panned(-1.0);
+        assert!((left - 1.0).abs() < 1e-6 && right.abs() < 1e-6);
+        // No step as the pan leaves the centre.
+        assert!((panned(0.0).0 - panned(0.01).0).abs() < 0.01);
+    }
+}
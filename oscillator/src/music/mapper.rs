@@ -1,6 +1,10 @@
 use tunes::prelude::*;
 
-use crate::models::{HarmonyQuality, MarketMetrics, MusicalParams};
+use crate::data::indicators::VolatilityMode;
+use crate::models::{
+    Accent, CrossDirection, HarmonyQuality, MarketEvent, MarketEventKind, MarketMetrics,
+    MarketRegime, MarketSession, MusicalParams, TimeframeMetrics, TradeSide,
+};
 
 use super::styles::CompositionStyle;
 
@@ -8,82 +12,384 @@ const MAJOR_PENT: [f32; 5] = [C4, D4, E4, G4, A4];
 const MINOR_PENT: [f32; 5] = [A3, C4, D4, E4, G4];
 const WHOLE_TONE: [f32; 6] = [C4, D4, E4, FS4, GS4, AS4];
 
+/// Chord progressions in semitones from the melody root, chosen by the medium
+/// timeframe: I–IV–V–I while it rises, a descending i–VII–VI–V while it falls
+/// and a I–IV vamp while it ranges.
+const RISING_PROGRESSION: [i32; 4] = [0, 5, 7, 0];
+const FALLING_PROGRESSION: [i32; 4] = [0, -2, -4, -5];
+const RANGING_PROGRESSION: [i32; 2] = [0, 5];
+
+/// Book imbalance beyond which a neutral RSI still resolves to suspended harmony.
+const TENSION_IMBALANCE: f64 = 0.6;
+
+/// Aggressor imbalance beyond which trade flow, not price change, sets melody direction.
+const FLOW_BIAS_IMBALANCE: f64 = 0.3;
+
+/// Filter opening while Bollinger bands are squeezed; the sound stays muffled
+/// until volatility breaks out of the range.
+const SQUEEZE_BRIGHTNESS: f32 = 0.35;
+
+/// Price-change z-score beyond which the melody moves to the whole-tone
+/// scale once the asset's moves are normalized; otherwise a ±5% move does.
+const WHOLE_TONE_Z: f64 = 2.0;
+
+/// Annualized volatility (in percent) at which reverb and distortion max out
+/// when volatility is not normalized; quiet equities sit near 20, crypto near 60.
+const ANNUALIZED_VOLATILITY_CEILING: f64 = 150.0;
+
+/// Tempo of the market-closed texture; slower than any trading-hours tempo.
+const CLOSED_TEMPO: f64 = 60.0;
+
+/// Pre- and post-market liquidity is thin, so those sessions play quieter.
+const EXTENDED_HOURS_INTENSITY: f64 = 0.6;
+
 pub struct MarketMapper {
     base_tempo: f64,
+    volatility_mode: VolatilityMode,
 }
 
 impl MarketMapper {
     pub fn new(base_tempo: f64) -> Self {
-        Self { base_tempo }
+        Self {
+            base_tempo,
+            volatility_mode: VolatilityMode::default(),
+        }
+    }
+
+    /// Scale of `metrics.volatility`, which the effects fall back to when the
+    /// asset's readings are not normalized.
+    pub fn with_volatility_mode(mut self, mode: VolatilityMode) -> Self {
+        self.volatility_mode = mode;
+        self
     }
 
     pub fn map(&self, metrics: &MarketMetrics, style: CompositionStyle) -> MusicalParams {
-        let (scale, ascending) = self.scale_for(metrics.price_change_percent);
-        let idx = self.scale_index(metrics.price_change_percent, scale.len());
-        let melody_note = if ascending {
-            scale[idx]
+        if metrics.session == Some(MarketSession::Closed) {
+            return self.map_closed(metrics, style);
+        }
+
+        let (scale, mut ascending) = self.scale_for(metrics);
+        let flow = metrics.flow.as_ref().map_or(0.0, |flow| flow.imbalance);
+        let flow_led = flow.abs() > FLOW_BIAS_IMBALANCE;
+        if flow_led {
+            ascending = flow > 0.0;
+        }
+
+        let idx = self.scale_index(metrics, scale.len());
+        let position = if ascending {
+            idx
         } else {
-            scale[scale.len() - idx - 1]
+            scale.len() - idx - 1
         };
+        let mut melody_notes = vec![scale[position]];
+        // One-sided aggression adds a passing note stepping the way the aggressors push.
+        if flow_led {
+            let next = if flow > 0.0 {
+                (position + 1).min(scale.len() - 1)
+            } else {
+                position.saturating_sub(1)
+            };
+            if next != position {
+                melody_notes.push(scale[next]);
+            }
+        }
 
-        let bass_note = self.bass_from_price(metrics.price);
+        let bass_note = self.bass_for(metrics);
 
         let tempo =
             self.base_tempo + (metrics.volume_ratio - 1.0) * 30.0 + metrics.tempo_bias * 40.0;
         let tempo = tempo.clamp(80.0, 160.0);
 
-        let harmony = if metrics.rsi < 30.0 {
+        let imbalance = metrics.book.as_ref().map_or(0.0, |book| book.imbalance);
+
+        // A warmed-up long timeframe sets the harmony; the primary RSI otherwise.
+        let rsi = long_timeframe(metrics)
+            .filter(|timeframe| timeframe.regime.is_some())
+            .map_or(metrics.rsi, |timeframe| timeframe.rsi);
+        let harmony = if rsi < 30.0 {
             HarmonyQuality::Minor
-        } else if metrics.rsi > 70.0 {
+        } else if rsi > 70.0 {
             HarmonyQuality::Diminished
+        } else if imbalance.abs() > TENSION_IMBALANCE {
+            HarmonyQuality::Suspended
         } else {
             HarmonyQuality::Major
         };
 
-        let reverb_mix = (metrics.volatility / 5.0).clamp(0.05, 0.7) as f32;
-        let distortion = (metrics.volatility / 3.0).clamp(0.0, 0.8) as f32;
+        // Bid-heavy books lean right, ask-heavy books lean left.
+        let pan = (imbalance * 0.8).clamp(-1.0, 1.0) as f32;
+
+        let (mut reverb_mix, distortion) = self.effects_for(metrics);
+        let brightness = match &metrics.bollinger {
+            Some(bands) if bands.squeeze => SQUEEZE_BRIGHTNESS,
+            _ => 1.0,
+        };
+        let mut volume_intensity = metrics.volume_ratio;
+        if matches!(
+            metrics.session,
+            Some(MarketSession::PreMarket | MarketSession::PostMarket)
+        ) {
+            volume_intensity *= EXTENDED_HOURS_INTENSITY;
+            reverb_mix = (reverb_mix + 0.15).min(0.85);
+        }
 
         MusicalParams {
             tempo,
-            melody_notes: vec![melody_note],
+            melody_notes,
             bass_note,
             harmony,
             reverb_mix,
             distortion,
-            volume_intensity: metrics.volume_ratio,
+            volume_intensity,
+            style: style.as_str().to_string(),
+            pan,
+            brightness,
+            accents: Vec::new(),
+            progression: self.progression_for(metrics),
+        }
+    }
+
+    /// One accent per kind of hit for the events since the last chunk: crashes
+    /// for new extremes and gaps, risers for volume spikes and stingers pointing
+    /// the way price, RSI or a large trade moved.
+    pub fn accents(&self, events: &[MarketEvent]) -> Vec<Accent> {
+        let mut accents = Vec::new();
+        for event in events {
+            let accent = match &event.kind {
+                MarketEventKind::SessionHigh
+                | MarketEventKind::SessionLow
+                | MarketEventKind::ReconnectGap { .. } => Accent::Crash,
+                MarketEventKind::VolumeSpike { .. } => Accent::Riser,
+                MarketEventKind::RoundNumber { direction, .. }
+                | MarketEventKind::RsiCross { direction, .. } => Accent::Stinger {
+                    rising: *direction == CrossDirection::Bullish,
+                },
+                MarketEventKind::LargeTrade { side, .. } => Accent::Stinger {
+                    rising: *side != Some(TradeSide::Sell),
+                },
+            };
+            if !accents.contains(&accent) {
+                accents.push(accent);
+            }
+        }
+        accents
+    }
+
+    /// Holds the last price as a suspended drone instead of sonifying stale indicators.
+    fn map_closed(&self, metrics: &MarketMetrics, style: CompositionStyle) -> MusicalParams {
+        let bass_note = self.bass_for(metrics);
+        MusicalParams {
+            tempo: CLOSED_TEMPO,
+            melody_notes: vec![bass_note * 4.0],
+            bass_note,
+            harmony: HarmonyQuality::Suspended,
+            reverb_mix: 0.8,
+            distortion: 0.0,
+            volume_intensity: 0.3,
             style: style.as_str().to_string(),
+            pan: 0.0,
+            brightness: 1.0,
+            accents: Vec::new(),
+            progression: Vec::new(),
         }
     }
 
-    fn scale_for(&self, price_change_percent: f64) -> (&'static [f32], bool) {
-        if price_change_percent > 5.0 || price_change_percent < -5.0 {
-            return (&WHOLE_TONE, price_change_percent >= 0.0);
+    /// Direction comes from the raw change; how extreme it is from the
+    /// asset's own history when normalized values are available. The long
+    /// timeframe's trend picks the key once it has one, so a dip inside an
+    /// uptrend stays major and only the melody turns downward.
+    fn scale_for(&self, metrics: &MarketMetrics) -> (&'static [f32], bool) {
+        let price_change_percent = metrics.price_change_percent;
+        let ascending = price_change_percent >= 0.0;
+        let extreme = match &metrics.normalized {
+            Some(normalized) => normalized.price_change.z_score.abs() > WHOLE_TONE_Z,
+            None => price_change_percent.abs() > 5.0,
+        };
+        if extreme {
+            return (&WHOLE_TONE, ascending);
         }
 
-        if price_change_percent >= 0.0 {
-            (&MAJOR_PENT, true)
+        let major = match long_timeframe(metrics).and_then(TimeframeMetrics::trend) {
+            Some(trend) => trend == CrossDirection::Bullish,
+            None => ascending,
+        };
+        if major {
+            (&MAJOR_PENT, ascending)
         } else {
-            (&MINOR_PENT, false)
+            (&MINOR_PENT, ascending)
+        }
+    }
+
+    /// The medium timeframe's progression; with fewer than two longer
+    /// timeframes the chord holds.
+    fn progression_for(&self, metrics: &MarketMetrics) -> Vec<i32> {
+        let Some(medium) = metrics
+            .timeframes
+            .len()
+            .checked_sub(2)
+            .map(|idx| &metrics.timeframes[idx])
+        else {
+            return Vec::new();
+        };
+        if medium.regime == Some(MarketRegime::Ranging) {
+            return RANGING_PROGRESSION.to_vec();
+        }
+        match medium.trend() {
+            Some(CrossDirection::Bullish) => RISING_PROGRESSION.to_vec(),
+            Some(CrossDirection::Bearish) => FALLING_PROGRESSION.to_vec(),
+            None => Vec::new(),
         }
     }
 
-    fn scale_index(&self, pct: f64, len: usize) -> usize {
+    fn scale_index(&self, metrics: &MarketMetrics, len: usize) -> usize {
         if len == 0 {
             return 0;
         }
-        let norm = (pct.abs() / 10.0).clamp(0.0, 1.0);
-        (norm * (len - 1) as f64).round() as usize
+        let norm = match &metrics.normalized {
+            Some(normalized) => normalized.price_change.z_score.abs() / WHOLE_TONE_Z,
+            None => metrics.price_change_percent.abs() / 10.0,
+        };
+        (norm.clamp(0.0, 1.0) * (len - 1) as f64).round() as usize
+    }
+
+    /// Reverb and distortion from volatility. Normalized, reverb follows the
+    /// volatility percentile and only above-median volatility distorts.
+    /// Raw annualized volatility is scaled against a fixed ceiling on the same
+    /// curve.
+    fn effects_for(&self, metrics: &MarketMetrics) -> (f32, f32) {
+        let level = match (&metrics.normalized, self.volatility_mode) {
+            (Some(normalized), _) => normalized.volatility.percentile,
+            (None, VolatilityMode::Annualized) => {
+                (metrics.volatility / ANNUALIZED_VOLATILITY_CEILING).clamp(0.0, 1.0)
+            }
+            (None, VolatilityMode::PerBar) => {
+                return (
+                    (metrics.volatility / 5.0).clamp(0.05, 0.7) as f32,
+                    (metrics.volatility / 3.0).clamp(0.0, 0.8) as f32,
+                );
+            }
+        };
+        let reverb = 0.05 + 0.65 * level;
+        let distortion = 0.8 * ((level - 0.5) * 2.0).max(0.0);
+        (reverb as f32, distortion as f32)
     }
 
-    fn bass_from_price(&self, price: f64) -> f32 {
+    /// Bass pitch from where the price sits in its recent range, or from its
+    /// order of magnitude before that range is known.
+    fn bass_for(&self, metrics: &MarketMetrics) -> f32 {
         const LOW: f32 = C1;
         const HIGH: f32 = C3;
 
-        if price <= 0.0 {
-            return LOW;
+        let position = match &metrics.normalized {
+            Some(normalized) => normalized.price.percentile,
+            None if metrics.price <= 0.0 => 0.0,
+            None => metrics.price.log10() / 5.0,
+        };
+        LOW + (HIGH - LOW) * position.clamp(0.0, 1.0) as f32
+    }
+}
+
+fn long_timeframe(metrics: &MarketMetrics) -> Option<&TimeframeMetrics> {
+    metrics.timeframes.last()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{NormalizedMetrics, NormalizedValue};
+
+    use super::*;
+
+    fn metrics(price: f64, price_change_percent: f64, volatility: f64) -> MarketMetrics {
+        MarketMetrics {
+            price,
+            price_change_percent,
+            volatility,
+            volume_ratio: 1.0,
+            rsi: 50.0,
+            ..MarketMetrics::default()
         }
+    }
+
+    fn ranked(z_score: f64, percentile: f64) -> NormalizedValue {
+        NormalizedValue {
+            z_score,
+            percentile,
+        }
+    }
+
+    #[test]
+    fn normalized_values_replace_absolute_thresholds() {
+        let mapper = MarketMapper::new(120.0);
+        let style = CompositionStyle::Electronic;
+
+        // 2% is unremarkable in absolute terms...
+        let mut spy = metrics(500.0, 2.0, 0.4);
+        let raw = mapper.map(&spy, style);
+        assert_ne!(raw.melody_notes[0], WHOLE_TONE[5]);
+
+        // ...but a three-sigma day for a quiet asset.
+        spy.normalized = Some(NormalizedMetrics {
+            price: ranked(2.5, 1.0),
+            price_change: ranked(3.0, 0.99),
+            volatility: ranked(2.0, 0.95),
+            volume_ratio: ranked(0.0, 0.5),
+        });
+        let params = mapper.map(&spy, style);
+        assert_eq!(params.melody_notes, vec![WHOLE_TONE[5]]);
+        assert_eq!(params.bass_note, C3);
+        assert!(params.distortion > raw.distortion);
+        // The raw readings are untouched.
+        assert_eq!(spy.price_change_percent, 2.0);
+    }
+
+    #[test]
+    fn annualized_volatility_keeps_headroom_without_normalization() {
+        let style = CompositionStyle::Electronic;
+        let annualized = MarketMapper::new(120.0).with_volatility_mode(VolatilityMode::Annualized);
+        let effects = |volatility| {
+            let params = annualized.map(&metrics(65_000.0, 0.0, volatility), style);
+            (params.reverb_mix, params.distortion)
+        };
+
+        // Typical readings stay well below the ceiling and clean.
+        let (equity_reverb, equity_distortion) = effects(20.0);
+        let (crypto_reverb, crypto_distortion) = effects(60.0);
+        assert!(equity_reverb < crypto_reverb && crypto_reverb < 0.4);
+        assert_eq!((equity_distortion, crypto_distortion), (0.0, 0.0));
+        // Only a turbulent market drives them, and only at the extreme do they pin.
+        let (reverb, distortion) = effects(120.0);
+        assert!(reverb < 0.7 && distortion > 0.0 && distortion < 0.8);
+        assert_eq!(effects(300.0), effects(150.0));
+
+        // Per-bar readings keep the original thresholds.
+        let per_bar = MarketMapper::new(120.0).map(&metrics(65_000.0, 0.0, 60.0), style);
+        assert_eq!((per_bar.reverb_mix, per_bar.distortion), (0.7, 0.8));
+    }
+
+    #[test]
+    fn longer_timeframes_set_key_harmony_and_progression() {
+        let mapper = MarketMapper::new(120.0);
+        let style = CompositionStyle::Electronic;
+        let timeframe = |label: &str, regime, rsi| TimeframeMetrics {
+            timeframe: label.to_string(),
+            rsi,
+            regime: Some(regime),
+            ..TimeframeMetrics::default()
+        };
+
+        // A short-term dip inside a longer uptrend.
+        let mut dip = metrics(100.0, -0.5, 0.4);
+        let without = mapper.map(&dip, style);
+        assert!(MINOR_PENT.contains(&without.melody_notes[0]));
+        assert!(without.progression.is_empty());
 
-        let normalized = (price.log10() / 5.0).clamp(0.0, 1.0) as f32;
-        LOW + (HIGH - LOW) * normalized
+        dip.timeframes = vec![
+            timeframe("15m", MarketRegime::TrendingDown, 45.0),
+            timeframe("1h", MarketRegime::TrendingUp, 75.0),
+        ];
+        let params = mapper.map(&dip, style);
+        assert_eq!(params.melody_notes, vec![MAJOR_PENT[4]]);
+        assert!(matches!(params.harmony, HarmonyQuality::Diminished));
+        assert_eq!(params.progression, FALLING_PROGRESSION);
     }
 }
@@ -1,7 +1,13 @@
+pub mod clock;
 pub mod composer;
 pub mod mapper;
+pub mod sections;
+pub mod stream;
 pub mod styles;
 
+pub use clock::RenderClock;
 pub use composer::MarketComposer;
 pub use mapper::MarketMapper;
+pub use sections::{SectionPlanner, SongSection};
+pub use stream::StreamingComposer;
 pub use styles::{CompositionStyle, StylePalette};
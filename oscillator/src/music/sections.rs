@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::MarketRegime;
+
+/// Chunks the intro plays before the first regime-driven section.
+const INTRO_CHUNKS: usize = 2;
+/// Chunks a section plays before it may change, so transitions land on phrase
+/// boundaries instead of following every regime flip.
+const MIN_SECTION_CHUNKS: usize = 4;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SongSection {
+    /// Sparse opening: melody and harmony only.
+    Intro,
+    /// The regular arrangement, for ranging markets.
+    Verse,
+    /// Full arrangement with a doubled melody, for trends and breakouts.
+    Chorus,
+    /// Rhythm section drops out, for capitulation.
+    Breakdown,
+}
+
+impl SongSection {
+    pub fn for_regime(regime: Option<MarketRegime>) -> Self {
+        match regime {
+            Some(
+                MarketRegime::TrendingUp | MarketRegime::TrendingDown | MarketRegime::Breakout,
+            ) => Self::Chorus,
+            Some(MarketRegime::Capitulation) => Self::Breakdown,
+            Some(MarketRegime::Ranging) | None => Self::Verse,
+        }
+    }
+
+    pub fn has_drums(&self) -> bool {
+        matches!(self, Self::Verse | Self::Chorus)
+    }
+
+    pub fn has_bass(&self) -> bool {
+        !matches!(self, Self::Intro)
+    }
+
+    pub fn doubles_melody(&self) -> bool {
+        matches!(self, Self::Chorus)
+    }
+
+    /// Sections that call for the palette's high-energy style.
+    pub fn is_energetic(&self) -> bool {
+        matches!(self, Self::Chorus)
+    }
+}
+
+/// Steps through song sections one chunk at a time: an intro, then whichever
+/// section the regime calls for, switching only after the current section has
+/// played out its minimum length.
+#[derive(Debug, Clone)]
+pub struct SectionPlanner {
+    section: SongSection,
+    chunks: usize,
+}
+
+impl Default for SectionPlanner {
+    fn default() -> Self {
+        Self {
+            section: SongSection::Intro,
+            chunks: 0,
+        }
+    }
+}
+
+impl SectionPlanner {
+    pub fn current(&self) -> SongSection {
+        self.section
+    }
+
+    /// Zero-based index of the current chunk within its section.
+    pub fn chunk_in_section(&self) -> usize {
+        self.chunks.saturating_sub(1)
+    }
+
+    /// Returns the section for the next chunk.
+    pub fn next(&mut self, regime: Option<MarketRegime>) -> SongSection {
+        let min_chunks = if self.section == SongSection::Intro {
+            INTRO_CHUNKS
+        } else {
+            MIN_SECTION_CHUNKS
+        };
+        let target = SongSection::for_regime(regime);
+        if target != self.section && self.chunks >= min_chunks {
+            self.section = target;
+            self.chunks = 0;
+        }
+        self.chunks += 1;
+        self.section
+    }
+
+    /// Starts over from the intro, e.g. when a closed market reopens.
+    pub fn restart(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intro_then_sections_follow_the_regime_on_phrase_boundaries() {
+        let mut planner = SectionPlanner::default();
+        let trend = Some(MarketRegime::TrendingUp);
+        let sections: Vec<_> = (0..4).map(|_| planner.next(trend)).collect();
+        assert_eq!(
+            sections,
+            [
+                SongSection::Intro,
+                SongSection::Intro,
+                SongSection::Chorus,
+                SongSection::Chorus
+            ]
+        );
+
+        // Capitulation waits for the chorus to finish its phrase.
+        let crash = Some(MarketRegime::Capitulation);
+        assert_eq!(planner.next(crash), SongSection::Chorus);
+        assert_eq!(planner.next(crash), SongSection::Chorus);
+        assert_eq!(planner.next(crash), SongSection::Breakdown);
+
+        planner.restart();
+        assert_eq!(planner.next(crash), SongSection::Intro);
+    }
+}
@@ -0,0 +1,216 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::{AudioChunk, MarketRegime, MusicalParams};
+
+use super::{
+    clock::chunk_seconds, composer::MarketComposer, sections::SongSection, styles::CompositionStyle,
+};
+
+const CHANNELS: usize = 2;
+
+/// Length of the fade-out applied when a carried tail is dropped, so a reset
+/// never cuts the stream off mid-waveform.
+const DECLICK_MS: f64 = 5.0;
+
+/// Wraps `MarketComposer` so consecutive chunks concatenate without seams.
+///
+/// Every chunk is trimmed or padded to exactly one chunk duration. Whatever the
+/// render produced past that point (reverb/delay tails, notes still sounding) is
+/// held back and mixed in full into the next chunk.
+///
+/// Each chunk is a fresh render, so voices restart at the boundary. An opt-in
+/// crossfade hands over from the tail to the new render's attack with linear
+/// (equal-gain) fades instead: the tail and the restarted voices are the same
+/// material, so the level stays flat across the join, but the tail is dropped
+/// after the crossfade window.
+pub struct StreamingComposer {
+    composer: MarketComposer,
+    crossfade_frames: usize,
+    declick_frames: usize,
+    tail: Vec<f32>,
+}
+
+impl StreamingComposer {
+    pub fn new(composer: MarketComposer, crossfade_ms: f64) -> Self {
+        let frames = |ms: f64| (ms.max(0.0) / 1000.0 * composer.sample_rate() as f64).round();
+        Self {
+            crossfade_frames: frames(crossfade_ms) as usize,
+            declick_frames: frames(DECLICK_MS) as usize,
+            composer,
+            tail: Vec::new(),
+        }
+    }
+
+    pub fn chunk_frames(&self, tempo: f64) -> usize {
+        let seconds = chunk_seconds(self.composer.bars_per_chunk(), tempo);
+        (seconds * self.composer.sample_rate() as f64).round() as usize
+    }
+
+    pub fn next_section(&mut self, regime: Option<MarketRegime>) -> SongSection {
+        self.composer.next_section(regime)
+    }
+
+    pub fn restart_song(&mut self) {
+        self.composer.restart_song();
+    }
+
+    /// Drops the carried tail, e.g. when the style changes, keeping only a
+    /// short fade-out of it so the next chunk does not start on a step.
+    pub fn reset(&mut self) {
+        let frames = self.declick_frames.max(self.crossfade_frames);
+        self.tail.truncate(frames * CHANNELS);
+        fade(&mut self.tail, frames, false);
+    }
+
+    pub fn render_next(
+        &mut self,
+        params: &MusicalParams,
+        style: CompositionStyle,
+        timestamp: DateTime<Utc>,
+    ) -> anyhow::Result<AudioChunk> {
+        let rendered = self.composer.render_buffer(params, style)?;
+        let len = self.chunk_frames(params.tempo) * CHANNELS;
+        let buffer = self.splice(rendered, len);
+        Ok(self.composer.encode_chunk(&buffer, timestamp))
+    }
+
+    /// Mixes the carried tail into `rendered` (or crossfades into it), returns
+    /// the first `len` samples and keeps the remainder as the next tail.
+    fn splice(&mut self, mut rendered: Vec<f32>, len: usize) -> Vec<f32> {
+        let mut tail = std::mem::take(&mut self.tail);
+        if self.crossfade_frames > 0 && !tail.is_empty() {
+            tail.truncate(self.crossfade_frames * CHANNELS);
+            fade(&mut tail, self.crossfade_frames, false);
+            fade(&mut rendered, self.crossfade_frames, true);
+        }
+
+        if rendered.len() < tail.len() {
+            rendered.resize(tail.len(), 0.0);
+        }
+        for (sample, carried) in rendered.iter_mut().zip(tail) {
+            *sample += carried;
+        }
+
+        if rendered.len() < len {
+            rendered.resize(len, 0.0);
+        }
+        self.tail = rendered.split_off(len);
+        rendered
+    }
+}
+
+/// Applies a linear fade in or out over the first `frames` frames of an
+/// interleaved buffer; a fade-in and fade-out over the same window sum to
+/// unity. A fade-out silences everything after the window.
+fn fade(buffer: &mut [f32], frames: usize, rising: bool) {
+    let window = frames.max(1) as f32;
+    for (frame, pair) in buffer.chunks_mut(CHANNELS).enumerate() {
+        let gain = if frame >= frames {
+            if rising { 1.0 } else { 0.0 }
+        } else {
+            let ramp = frame as f32 / window;
+            if rising { ramp } else { 1.0 - ramp }
+        };
+        pair.iter_mut().for_each(|s| *s *= gain);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tunes::prelude::*;
+
+    use super::*;
+
+    fn samples(chunk: &AudioChunk) -> Vec<f32> {
+        chunk
+            .samples
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect()
+    }
+
+    /// Largest jump between consecutive samples of one channel.
+    fn max_step(samples: &[f32], channel: usize) -> f32 {
+        samples
+            .iter()
+            .skip(channel)
+            .step_by(CHANNELS)
+            .collect::<Vec<_>>()
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn splice_joins_chunks_without_a_step() {
+        let mut stream = StreamingComposer::new(MarketComposer::new(44_100, 1), 0.0);
+        let params = MusicalParams {
+            // More melody than fits in the bar, so notes ring on into the tail.
+            melody_notes: vec![C4, E4, G4, C5, G4, E4, C4, E4, G4, C5, G4, E4],
+            bass_note: C2,
+            reverb_mix: 0.5,
+            ..MusicalParams::default()
+        };
+        let at = Utc::now();
+        let first = samples(
+            &stream
+                .render_next(&params, CompositionStyle::Electronic, at)
+                .unwrap(),
+        );
+        // Voices still sounding in the carried tail, cut against a fresh render.
+        assert!(stream.tail.iter().any(|s| s.abs() > 1e-3));
+        let second = samples(
+            &stream
+                .render_next(&params, CompositionStyle::Electronic, at)
+                .unwrap(),
+        );
+
+        for channel in 0..CHANNELS {
+            let last = first[first.len() - CHANNELS + channel];
+            let join = (second[channel] - last).abs();
+            let within = max_step(&first, channel).max(max_step(&second, channel));
+            assert!(join <= within, "join {join} vs largest step {within}");
+        }
+    }
+
+    #[test]
+    fn decaying_tails_carry_past_the_crossfade_window() {
+        let mut stream = StreamingComposer::new(MarketComposer::new(44_100, 1), 0.0);
+        // Half a second of reverb decaying from 0.5, well past any crossfade window.
+        let decay = |frame: usize| 0.5 * (-(frame as f32) / 8_000.0).exp();
+        stream.tail = (0..22_050)
+            .flat_map(|frame| [decay(frame); CHANNELS])
+            .collect();
+        let spliced = stream.splice(vec![0.0; 44_100 * CHANNELS], 44_100 * CHANNELS);
+        for frame in [0, 441, 4_410, 22_049] {
+            assert_eq!(spliced[frame * CHANNELS], decay(frame), "frame {frame}");
+        }
+        assert!(spliced[22_050 * CHANNELS..].iter().all(|s| *s == 0.0));
+    }
+
+    #[test]
+    fn crossfade_restarts_a_held_note_at_a_steady_level() {
+        let mut stream = StreamingComposer::new(MarketComposer::new(44_100, 1), 10.0);
+        // The previous chunk ended on a held 0.8 that rings on in the tail,
+        // and the next render restarts the same note at full level.
+        stream.tail = vec![0.8; 2_000 * CHANNELS];
+        let spliced = stream.splice(vec![0.8; 4_000 * CHANNELS], 4_000 * CHANNELS);
+        // Correlated material must neither dip nor swell across the window.
+        let deviation = spliced
+            .iter()
+            .map(|sample| (sample - 0.8).abs())
+            .fold(0.0, f32::max);
+        assert!(deviation < 1e-5, "{deviation}");
+        assert!(stream.tail.is_empty());
+    }
+
+    #[test]
+    fn reset_fades_the_tail_out() {
+        let mut stream = StreamingComposer::new(MarketComposer::new(44_100, 1), 0.0);
+        stream.tail = vec![0.5; 44_100 * CHANNELS];
+        stream.reset();
+        assert_eq!(stream.tail.len(), stream.declick_frames * CHANNELS);
+        assert_eq!(stream.tail[0], 0.5);
+        assert!(stream.tail.last().unwrap().abs() < 0.01);
+    }
+}
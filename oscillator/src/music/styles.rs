@@ -2,12 +2,16 @@ use serde::{Deserialize, Serialize};
 
 use crate::models::AssetCategory;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+use super::sections::SongSection;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum CompositionStyle {
     Electronic,
     Orchestral,
     Ambient,
     Rock,
+    /// Static texture for exchanges outside trading hours; chosen by the engine, not the palette.
+    MarketClosed,
 }
 
 impl CompositionStyle {
@@ -17,6 +21,7 @@ impl CompositionStyle {
             Self::Orchestral => "Orchestral",
             Self::Ambient => "Ambient",
             Self::Rock => "Rock",
+            Self::MarketClosed => "MarketClosed",
         }
     }
 }
@@ -39,21 +44,23 @@ impl StylePalette {
         }
     }
 
-    pub fn style_for_category(
+    /// High-energy sections switch to each category's livelier style.
+    pub fn style_for_section(
         &self,
         category: AssetCategory,
-        high_volatility: bool,
+        section: SongSection,
     ) -> CompositionStyle {
+        let energetic = section.is_energetic();
         match category {
             AssetCategory::Crypto => {
-                if high_volatility {
+                if energetic {
                     self.crypto_primary
                 } else {
                     self.crypto_alt
                 }
             }
             AssetCategory::Stock => {
-                if high_volatility {
+                if energetic {
                     self.stock_alt
                 } else {
                     self.stock_primary
@@ -1,29 +1,83 @@
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
 
+use chrono::Utc;
+use chrono_tz::Tz;
 use parking_lot::RwLock;
-use tokio::sync::broadcast;
-use tracing::{error, info, warn};
+use tokio::{
+    sync::broadcast,
+    time::{sleep_until, timeout},
+};
+use tracing::{debug, error, info, warn};
 
 use crate::{
     data::{
+        anchor::{ChangeAnchor, PriceAnchor},
+        backoff::ReconnectPolicy,
+        calendar::TradingCalendar,
+        coinbase::CoinbaseClient,
         crypto::BinanceClient,
-        indicators::IndicatorCalculator,
+        events::EventConfig,
+        filter::{FilterConfig, TickFilter},
+        health::FeedHealthStore,
+        indicators::{
+            DEFAULT_BAR_SECS, DEFAULT_TIMEFRAMES, IndicatorCalculator, IndicatorPeriods, Timeframe,
+        },
+        kraken::KrakenClient,
+        normalize::MetricNormalizer,
+        orderbook::OrderBookStore,
+        recorder::{RecordFormat, RecorderConfig, RecorderHandle},
+        regime::RegimeConfig,
+        replay::ReplaySource,
+        signals::IndicatorRegistry,
+        simulator::SimulatorSource,
+        source::{HistoryRequest, MarketSource, SourceRegistry},
         stocks::YahooFinanceClient,
         streams::{self, TickReceiver},
     },
-    models::{AssetCategory, AssetDescriptor, AudioPacket, MarketMetrics},
-    music::{MarketComposer, MarketMapper, StylePalette},
+    models::{
+        AssetCategory, AssetDescriptor, AudioPacket, FeedState, FeedStatus, MarketMetrics,
+        MarketSession, PriceTick, SourceSpec,
+    },
+    music::{
+        CompositionStyle, MarketComposer, MarketMapper, RenderClock, StreamingComposer,
+        StylePalette,
+    },
 };
 
+/// Closed bars fetched to warm indicators unless an asset sets its own `warmup`.
+const DEFAULT_WARMUP_BARS: usize = 120;
+const WARMUP_TIMEOUT: Duration = Duration::from_secs(10);
+/// Chunk joins carry the whole tail unless `OSC_CROSSFADE_MS` opts into a crossfade.
+const DEFAULT_CROSSFADE_MS: f64 = 0.0;
+/// Yahoo emits one bar a minute at best, so its assets tolerate longer silences.
+const YAHOO_STALE_SECS: u64 = 180;
+
 #[derive(Clone)]
 pub struct AppConfig {
     pub host: String,
     pub port: u16,
     pub binance_ws: String,
+    pub binance_rest: String,
+    pub coinbase_ws: String,
+    pub kraken_ws: String,
     pub yahoo_base: String,
     pub sample_rate: u32,
     pub chunk_bars: usize,
     pub base_tempo: f64,
+    pub crossfade_ms: f64,
+    pub recorder: Option<RecorderConfig>,
+    pub calendar: TradingCalendar,
+    pub stale_after: Duration,
+    /// Length of the bars indicators run on unless an asset sets `bar_secs`.
+    pub bar_duration: Duration,
+    /// Reconnect policies by source name; sources not listed use the default.
+    pub reconnect: HashMap<String, ReconnectPolicy>,
     pub assets: Vec<AssetDescriptor>,
 }
 
@@ -36,6 +90,12 @@ impl AppConfig {
             .unwrap_or(8080);
         let binance_ws = std::env::var("OSC_BINANCE_WS")
             .unwrap_or_else(|_| "wss://stream.binance.com:9443".into());
+        let binance_rest =
+            std::env::var("OSC_BINANCE_REST").unwrap_or_else(|_| "https://api.binance.com".into());
+        let coinbase_ws = std::env::var("OSC_COINBASE_WS")
+            .unwrap_or_else(|_| "wss://ws-feed.exchange.coinbase.com".into());
+        let kraken_ws =
+            std::env::var("OSC_KRAKEN_WS").unwrap_or_else(|_| "wss://ws.kraken.com/v2".into());
         let yahoo_base = std::env::var("OSC_YAHOO_BASE")
             .unwrap_or_else(|_| "https://query1.finance.yahoo.com".into());
         let sample_rate = std::env::var("OSC_SAMPLE_RATE")
@@ -50,19 +110,97 @@ impl AppConfig {
             .ok()
             .and_then(|v| v.parse().ok())
             .unwrap_or(104.0);
+        let assets = std::env::var("OSC_ASSETS")
+            .ok()
+            .and_then(|path| match load_assets(&path) {
+                Ok(assets) => Some(assets),
+                Err(err) => {
+                    warn!(%path, %err, "Failed to load asset config - using defaults");
+                    None
+                }
+            })
+            .unwrap_or_else(default_assets);
+        let calendar = std::env::var("OSC_CALENDAR")
+            .ok()
+            .and_then(|path| match load_calendar(&path) {
+                Ok(calendar) => Some(calendar),
+                Err(err) => {
+                    warn!(%path, %err, "Failed to load trading calendar - using NYSE defaults");
+                    None
+                }
+            })
+            .unwrap_or_default();
+        let stale_after = std::env::var("OSC_STALE_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs_f64)
+            .unwrap_or(Duration::from_secs(60));
+        let bar_duration = std::env::var("OSC_BAR_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs_f64)
+            .unwrap_or(Duration::from_secs(DEFAULT_BAR_SECS as u64));
+        let reconnect = std::env::var("OSC_RECONNECT")
+            .ok()
+            .and_then(|path| match load_reconnect(&path) {
+                Ok(reconnect) => Some(reconnect),
+                Err(err) => {
+                    warn!(%path, %err, "Failed to load reconnect policies - using defaults");
+                    None
+                }
+            })
+            .unwrap_or_default();
+        let crossfade_ms = std::env::var("OSC_CROSSFADE_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_CROSSFADE_MS);
+        let recorder = std::env::var("OSC_RECORD_DIR")
+            .ok()
+            .map(|dir| RecorderConfig {
+                dir: PathBuf::from(dir),
+                format: std::env::var("OSC_RECORD_FORMAT")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(RecordFormat::Jsonl),
+                max_bytes: std::env::var("OSC_RECORD_MAX_MB")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .map(|mb| mb * 1024 * 1024),
+                max_age: std::env::var("OSC_RECORD_MAX_MINUTES")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .map(|mins| Duration::from_secs(mins * 60)),
+                compress: std::env::var("OSC_RECORD_COMPRESS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(false),
+            });
 
         Self {
             host,
             port,
             binance_ws,
+            binance_rest,
+            coinbase_ws,
+            kraken_ws,
             yahoo_base,
             sample_rate,
             chunk_bars,
             base_tempo,
-            assets: default_assets(),
+            crossfade_ms,
+            recorder,
+            calendar,
+            stale_after,
+            bar_duration,
+            reconnect,
+            assets,
         }
     }
 
+    pub fn reconnect_policy(&self, source: &str) -> ReconnectPolicy {
+        self.reconnect.get(source).cloned().unwrap_or_default()
+    }
+
     pub fn addr(&self) -> SocketAddr {
         SocketAddr::new(
             self.host
@@ -88,6 +226,8 @@ struct AppStateInner {
     config: AppConfig,
     assets: Vec<AssetDescriptor>,
     metrics: RwLock<HashMap<String, MarketMetrics>>,
+    order_books: OrderBookStore,
+    feed_health: FeedHealthStore,
     broadcasters: RwLock<HashMap<String, broadcast::Sender<AudioPacket>>>,
 }
 
@@ -104,6 +244,8 @@ impl AppState {
                 assets: config.assets.clone(),
                 config,
                 metrics: RwLock::new(HashMap::new()),
+                order_books: OrderBookStore::new(),
+                feed_health: FeedHealthStore::new(),
                 broadcasters: RwLock::new(broadcasters),
             }),
         }
@@ -117,6 +259,20 @@ impl AppState {
         &self.inner.assets
     }
 
+    pub fn order_books(&self) -> &OrderBookStore {
+        &self.inner.order_books
+    }
+
+    pub fn feed_health(&self) -> &FeedHealthStore {
+        &self.inner.feed_health
+    }
+
+    /// Feed health of a catalog asset, looked up under the source it streams from.
+    pub fn feed_status(&self, symbol: &str) -> Option<FeedStatus> {
+        let asset = self.assets().iter().find(|asset| asset.symbol == symbol)?;
+        self.feed_health().status(&asset.source.name, symbol)
+    }
+
     pub fn latest_metrics(&self, symbol: &str) -> Option<MarketMetrics> {
         self.inner.metrics.read().get(symbol).cloned()
     }
@@ -147,22 +303,32 @@ impl AppState {
 
 pub struct MarketEngine {
     state: AppState,
-    binance: BinanceClient,
-    yahoo: YahooFinanceClient,
+    sources: SourceRegistry,
+    indicators: IndicatorRegistry,
     palette: StylePalette,
 }
 
 impl MarketEngine {
     pub fn new(state: AppState) -> Self {
-        let config = state.config().clone();
+        let sources = default_sources(&state);
+        Self::with_sources(state, sources)
+    }
+
+    pub fn with_sources(state: AppState, sources: SourceRegistry) -> Self {
         Self {
-            binance: BinanceClient::new(config.binance_ws.clone()),
-            yahoo: YahooFinanceClient::new(config.yahoo_base.clone()),
+            sources,
+            indicators: IndicatorRegistry::builtin(),
             palette: StylePalette::default(),
             state,
         }
     }
 
+    /// Replaces the registry that assets' `indicators` entries are built from.
+    pub fn with_indicators(mut self, indicators: IndicatorRegistry) -> Self {
+        self.indicators = indicators;
+        self
+    }
+
     pub fn spawn(&self) {
         for asset in self.state.assets() {
             self.spawn_asset(asset.clone());
@@ -170,64 +336,337 @@ impl MarketEngine {
     }
 
     fn spawn_asset(&self, asset: AssetDescriptor) {
+        let Some(source) = self.sources.get(&asset.source.name) else {
+            error!(symbol = %asset.symbol, source = %asset.source.name, "Unknown market source");
+            return;
+        };
+
+        let stale_after = asset
+            .source
+            .parse_option("stale_secs")
+            .map(Duration::from_secs_f64)
+            .unwrap_or(self.state.config().stale_after);
+        let health = self.state.feed_health().clone();
+        health.register(source.name(), &asset.symbol, stale_after);
+
         let (tx, rx) = streams::channel(512);
-        match asset.category {
-            AssetCategory::Crypto => {
-                self.binance.spawn_trade_stream(asset.symbol.clone(), tx);
-            }
-            AssetCategory::Stock => {
-                self.yahoo.spawn_price_poller(asset.symbol.clone(), tx, 15);
+        let feed_asset = asset.clone();
+        let history_source = source.clone();
+        tokio::spawn(async move {
+            let symbol = feed_asset.symbol.clone();
+            if let Err(err) = source.run(feed_asset, tx).await {
+                error!(%symbol, source = source.name(), %err, "Market source stopped");
+                health.record_error(source.name(), &symbol, &err);
             }
-        }
+            health.set_state(source.name(), &symbol, FeedState::Disconnected);
+        });
 
-        let composer = MarketComposer::new(
-            self.state.config().sample_rate,
-            self.state.config().chunk_bars,
+        let composer = StreamingComposer::new(
+            MarketComposer::new(
+                self.state.config().sample_rate,
+                self.state.config().chunk_bars,
+            ),
+            self.state.config().crossfade_ms,
         );
-        let mapper = MarketMapper::new(self.state.config().base_tempo);
+        let indicators = self.indicator_calculator(&asset);
         let palette = self.palette.clone();
         let state = self.state.clone();
 
         tokio::spawn(async move {
-            run_pipeline(asset, rx, mapper, composer, palette, state).await;
+            run_pipeline(
+                asset,
+                history_source,
+                rx,
+                indicators,
+                composer,
+                palette,
+                state,
+            )
+            .await;
         });
     }
+
+    fn indicator_calculator(&self, asset: &AssetDescriptor) -> IndicatorCalculator {
+        let config = self.state.config();
+        let bar_duration = asset
+            .source
+            .parse_option("bar_secs")
+            .map(Duration::from_secs_f64)
+            .unwrap_or(config.bar_duration);
+        // VWAP and the price-change anchor follow the exchange's trading day;
+        // 24/7 markets use UTC and a rolling 24h change.
+        let calendar = (asset.category == AssetCategory::Stock).then(|| config.calendar.clone());
+        let change_anchor =
+            asset
+                .source
+                .parse_option("change_anchor")
+                .unwrap_or(if calendar.is_some() {
+                    ChangeAnchor::SessionOpen
+                } else {
+                    ChangeAnchor::default()
+                });
+        let mut calculator = IndicatorCalculator::new(&asset.symbol, 14, 512)
+            .with_bar_duration(
+                chrono::Duration::from_std(bar_duration)
+                    .unwrap_or(chrono::Duration::seconds(DEFAULT_BAR_SECS)),
+            )
+            .with_rsi_mode(asset.source.parse_option("rsi").unwrap_or_default())
+            .with_volatility_mode(asset.source.parse_option("volatility").unwrap_or_default())
+            .with_periods(&IndicatorPeriods::from_spec(&asset.source))
+            .with_timeframes(&Timeframe::parse_list(
+                asset
+                    .source
+                    .option("timeframes")
+                    .unwrap_or(DEFAULT_TIMEFRAMES),
+            ))
+            .with_regime(RegimeConfig::from_spec(&asset.source))
+            .with_events(EventConfig::from_spec(&asset.source))
+            .with_normalizer(MetricNormalizer::from_spec(&asset.source))
+            .with_session_timezone(
+                calendar
+                    .as_ref()
+                    .map_or(Tz::UTC, |calendar| calendar.timezone),
+            )
+            .with_change_anchor(PriceAnchor::new(change_anchor, calendar));
+        for spec in &asset.indicators {
+            match self.indicators.build(spec) {
+                Ok(indicator) => calculator = calculator.with_indicator(spec.name(), indicator),
+                Err(err) => {
+                    warn!(symbol = %asset.symbol, kind = %spec.kind, %err, "Skipping indicator");
+                }
+            }
+        }
+        calculator
+    }
 }
 
 async fn run_pipeline(
     asset: AssetDescriptor,
+    source: Arc<dyn MarketSource>,
     mut rx: TickReceiver,
-    mapper: MarketMapper,
-    composer: MarketComposer,
+    mut indicators: IndicatorCalculator,
+    mut composer: StreamingComposer,
     palette: StylePalette,
     state: AppState,
 ) {
-    let mut indicators = IndicatorCalculator::new(&asset.symbol, 14, 512);
+    // Equities follow the exchange calendar; everything else trades around the clock.
+    let calendar =
+        (asset.category == AssetCategory::Stock).then(|| state.config().calendar.clone());
+    let mapper = MarketMapper::new(state.config().base_tempo)
+        .with_volatility_mode(asset.source.parse_option("volatility").unwrap_or_default());
+    let mut clock = RenderClock::new(state.config().chunk_bars);
+    let mut filter = TickFilter::new(FilterConfig::from_spec(&asset.source));
+    // The source is already streaming, so live ticks are held back while the
+    // history loads instead of backing up the channel until the source drops them.
+    let mut backlog = VecDeque::new();
+    let mut latest = {
+        let warm = warm_up(source.as_ref(), &asset, &mut filter, &mut indicators);
+        tokio::pin!(warm);
+        loop {
+            tokio::select! {
+                latest = &mut warm => break latest,
+                Some(tick) = rx.recv() => backlog.push_back(tick),
+            }
+        }
+    };
+    // Live ticks the warm-up history already covers are skipped so bars are not counted twice.
+    let warmed_until = latest.as_ref().map(|metrics| metrics.last_updated);
+    if latest.is_some() {
+        clock.reset();
+    }
+    let mut recorder = state
+        .config()
+        .recorder
+        .clone()
+        .map(|config| RecorderHandle::spawn(config, &asset.symbol));
+    // Events since the last rendered chunk, and reconnects seen so far so a
+    // gap across the next one can be reported.
+    let mut events = Vec::new();
+    let mut reconnects = state
+        .feed_health()
+        .reconnects(&asset.source.name, &asset.symbol);
+    let mut last_style = None;
     info!(symbol = %asset.symbol, "Pipeline started");
 
-    while let Some(tick) = rx.recv().await {
-        let metrics = indicators.on_tick(&tick);
-        let style = palette.style_for_category(asset.category.clone(), metrics.volatility >= 2.5);
-        let params = mapper.map(&metrics, style);
-
-        match composer.render_chunk(&params, style) {
-            Ok(chunk) => {
-                state.publish(AudioPacket {
-                    asset: asset.symbol.clone(),
-                    metrics: metrics.clone(),
-                    params: params.clone(),
-                    chunk,
-                });
+    loop {
+        tokio::select! {
+            tick = async {
+                match backlog.pop_front() {
+                    Some(tick) => Some(tick),
+                    None => rx.recv().await,
+                }
+            } => {
+                let Some(tick) = tick else { break };
+                state.feed_health().record_tick(&asset.source.name, &asset.symbol);
+                if let Some(rec) = recorder.as_mut()
+                    && let Err(err) = rec.record(&tick)
+                {
+                    error!(symbol = %asset.symbol, %err, "Tick recorder failed - disabling");
+                    recorder = None;
+                }
+                if warmed_until.is_some_and(|until| tick.timestamp <= until) {
+                    continue;
+                }
+                if let Err(reason) = filter.check(&tick) {
+                    debug!(symbol = %asset.symbol, ?reason, price = tick.price, "Tick rejected");
+                    state.feed_health()
+                        .record_rejection(&asset.source.name, &asset.symbol, reason);
+                    continue;
+                }
+                if latest.is_none() {
+                    clock.reset();
+                }
+                let seen = state.feed_health().reconnects(&asset.source.name, &asset.symbol);
+                if seen != reconnects {
+                    reconnects = seen;
+                    indicators.reconnected();
+                }
+                latest = Some(indicators.on_tick(&tick));
+                events.extend(indicators.take_events());
             }
-            Err(err) => {
-                error!(symbol = %asset.symbol, %err, "composer failure");
+            _ = sleep_until(clock.deadline()), if latest.is_some() => {
+                let Some(mut metrics) = latest.clone() else { continue };
+                metrics.book = state.order_books().latest(&asset.symbol);
+                metrics.session = calendar
+                    .as_ref()
+                    .map(|calendar| calendar.session_at(Utc::now()));
+                let style = if metrics.session == Some(MarketSession::Closed) {
+                    composer.restart_song();
+                    CompositionStyle::MarketClosed
+                } else {
+                    let section = composer.next_section(metrics.regime);
+                    palette.style_for_section(asset.category.clone(), section)
+                };
+                // A new style brings new instruments; fade the old ones out
+                // instead of carrying their tail under the new chunk.
+                if last_style.replace(style).is_some_and(|last| last != style) {
+                    composer.reset();
+                }
+                let events = std::mem::take(&mut events);
+                let mut params = mapper.map(&metrics, style);
+                params.accents = mapper.accents(&events);
+                let timestamp = clock.advance(params.tempo);
+
+                match composer.render_next(&params, style, timestamp) {
+                    Ok(chunk) => {
+                        state.publish(AudioPacket {
+                            asset: asset.symbol.clone(),
+                            metrics,
+                            params,
+                            events,
+                            chunk,
+                        });
+                    }
+                    Err(err) => {
+                        error!(symbol = %asset.symbol, %err, "composer failure");
+                    }
+                }
             }
         }
     }
 
+    if let Some(rec) = recorder {
+        rec.close().await;
+    }
     warn!(symbol = %asset.symbol, "Pipeline terminated");
 }
 
+/// Seeds `filter` and `indicators` from the source's recent history. Returns the
+/// warmed metrics, or `None` when warm-up is disabled or no history could be fetched.
+async fn warm_up(
+    source: &dyn MarketSource,
+    asset: &AssetDescriptor,
+    filter: &mut TickFilter,
+    indicators: &mut IndicatorCalculator,
+) -> Option<MarketMetrics> {
+    let bars = asset
+        .source
+        .parse_option("warmup")
+        .unwrap_or(DEFAULT_WARMUP_BARS);
+    if bars == 0 {
+        return None;
+    }
+
+    let request = HistoryRequest::new(
+        bars,
+        indicators.bar_duration(),
+        indicators.longest_timeframe(),
+    );
+    let history = match timeout(WARMUP_TIMEOUT, source.history(asset, &request)).await {
+        Ok(Ok(history)) => history,
+        Ok(Err(err)) => {
+            warn!(symbol = %asset.symbol, %err, "Warm-up history failed - starting cold");
+            return None;
+        }
+        Err(_) => {
+            warn!(symbol = %asset.symbol, "Warm-up history timed out - starting cold");
+            return None;
+        }
+    };
+    let fetched = history.len();
+    let history: Vec<PriceTick> = history
+        .into_iter()
+        .filter(|tick| filter.check(tick).is_ok())
+        .collect();
+    if !history.is_empty() {
+        info!(
+            symbol = %asset.symbol,
+            bars = history.len(),
+            rejected = fetched - history.len(),
+            "Indicators warmed from history"
+        );
+    }
+    indicators.warm_up(&history)
+}
+
+fn default_sources(state: &AppState) -> SourceRegistry {
+    let config = state.config();
+    let health = state.feed_health();
+    let binance = BinanceClient::new(config.binance_ws.clone())
+        .with_rest_base(config.binance_rest.clone())
+        .with_order_books(state.order_books().clone())
+        .with_health(health.clone())
+        .with_reconnect(config.reconnect_policy("binance"));
+
+    let mut sources = SourceRegistry::new();
+    sources
+        .register(Arc::new(binance))
+        .register(Arc::new(
+            CoinbaseClient::new(config.coinbase_ws.clone())
+                .with_health(health.clone())
+                .with_reconnect(config.reconnect_policy("coinbase")),
+        ))
+        .register(Arc::new(
+            KrakenClient::new(config.kraken_ws.clone())
+                .with_health(health.clone())
+                .with_reconnect(config.reconnect_policy("kraken")),
+        ))
+        .register(Arc::new(
+            YahooFinanceClient::new(config.yahoo_base.clone())
+                .with_calendar(config.calendar.clone())
+                .with_health(health.clone())
+                .with_reconnect(config.reconnect_policy("yahoo")),
+        ))
+        .register(Arc::new(ReplaySource::new()))
+        .register(Arc::new(SimulatorSource::new()));
+    sources
+}
+
+fn load_calendar(path: &str) -> anyhow::Result<TradingCalendar> {
+    let raw = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&raw)?)
+}
+
+fn load_reconnect(path: &str) -> anyhow::Result<HashMap<String, ReconnectPolicy>> {
+    let raw = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&raw)?)
+}
+
+fn load_assets(path: &str) -> anyhow::Result<Vec<AssetDescriptor>> {
+    let raw = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&raw)?)
+}
+
 fn default_assets() -> Vec<AssetDescriptor> {
     vec![
         AssetDescriptor {
@@ -236,6 +675,8 @@ fn default_assets() -> Vec<AssetDescriptor> {
             category: AssetCategory::Crypto,
             description: "Bitcoin vs Tether spot market (Binance)".to_string(),
             tick_size: 0.01,
+            source: SourceSpec::new("binance"),
+            indicators: Vec::new(),
         },
         AssetDescriptor {
             symbol: "ethusdt".to_string(),
@@ -243,6 +684,8 @@ fn default_assets() -> Vec<AssetDescriptor> {
             category: AssetCategory::Crypto,
             description: "Ethereum vs Tether".to_string(),
             tick_size: 0.01,
+            source: SourceSpec::new("binance"),
+            indicators: Vec::new(),
         },
         AssetDescriptor {
             symbol: "solusdt".to_string(),
@@ -250,6 +693,8 @@ fn default_assets() -> Vec<AssetDescriptor> {
             category: AssetCategory::Crypto,
             description: "Solana vs Tether".to_string(),
             tick_size: 0.01,
+            source: SourceSpec::new("binance"),
+            indicators: Vec::new(),
         },
         AssetDescriptor {
             symbol: "AAPL".to_string(),
@@ -257,6 +702,8 @@ fn default_assets() -> Vec<AssetDescriptor> {
             category: AssetCategory::Stock,
             description: "Apple equity (NASDAQ)".to_string(),
             tick_size: 0.01,
+            source: SourceSpec::new("yahoo").with_option("stale_secs", YAHOO_STALE_SECS),
+            indicators: Vec::new(),
         },
         AssetDescriptor {
             symbol: "TSLA".to_string(),
@@ -264,6 +711,8 @@ fn default_assets() -> Vec<AssetDescriptor> {
             category: AssetCategory::Stock,
             description: "Tesla equity (NASDAQ)".to_string(),
             tick_size: 0.01,
+            source: SourceSpec::new("yahoo").with_option("stale_secs", YAHOO_STALE_SECS),
+            indicators: Vec::new(),
         },
         AssetDescriptor {
             symbol: "SPY".to_string(),
@@ -271,6 +720,8 @@ fn default_assets() -> Vec<AssetDescriptor> {
             category: AssetCategory::Stock,
             description: "SPDR S&P 500 ETF".to_string(),
             tick_size: 0.01,
+            source: SourceSpec::new("yahoo").with_option("stale_secs", YAHOO_STALE_SECS),
+            indicators: Vec::new(),
         },
     ]
 }